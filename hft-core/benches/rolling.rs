@@ -0,0 +1,76 @@
+//! Per-update cost of a rolling mean/std/covariance: the ring-buffer
+//! [`RollingCov`] against dropping the oldest sample with `Vec::remove(0)`
+//! and recomputing from a copy of the window, across window lengths.
+//!
+//! `cargo bench -p hft-core --bench rolling`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use hft_core::{Rolling, RollingCov};
+
+const LOOKBACKS: [usize; 4] = [32, 256, 2048, 16384];
+
+/// A deterministic walk, so every run sees the same numbers; different
+/// seeds give independent legs.
+fn walk(n: usize, seed: u64) -> Vec<f64> {
+    let mut x = 4.6;
+    let mut s = seed;
+    (0..n).map(|_| {
+        s = s.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        x += ((s >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.002;
+        x
+    }).collect()
+}
+
+fn single(c: &mut Criterion) {
+    let mut g = c.benchmark_group("rolling_std");
+    for look in LOOKBACKS {
+        let xs = walk(look * 2, 42);
+
+        g.bench_with_input(BenchmarkId::new("ring", look), &look, |b, &look| {
+            let mut w = Rolling::new(look);
+            xs.iter().for_each(|&x| w.push(x));
+            let mut k = 0;
+            b.iter(|| {
+                w.push(xs[k % xs.len()]);
+                k += 1;
+                black_box((w.mean(), w.std()))
+            });
+        });
+
+        g.bench_with_input(BenchmarkId::new("vec_remove", look), &look, |b, &look| {
+            let mut w: Vec<f64> = xs[..look].to_vec();
+            let mut k = 0;
+            b.iter(|| {
+                w.remove(0);
+                w.push(xs[k % xs.len()]);
+                k += 1;
+                let copy = w.clone();
+                let n = copy.len() as f64;
+                let mean = copy.iter().sum::<f64>() / n;
+                let var = copy.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+                black_box((mean, var.sqrt()))
+            });
+        });
+    }
+    g.finish();
+}
+
+fn pair(c: &mut Criterion) {
+    let mut g = c.benchmark_group("rolling_cov2");
+    for look in LOOKBACKS {
+        let (a, b) = (walk(look * 2, 42), walk(look * 2, 7));
+        g.bench_with_input(BenchmarkId::new("ring", look), &look, |bench, &look| {
+            let mut w = RollingCov::new(2, look);
+            let mut k = 0;
+            bench.iter(|| {
+                w.push(&[a[k % a.len()], b[k % b.len()]]);
+                k += 1;
+                black_box(w.cov(0, 1) / w.var(1))
+            });
+        });
+    }
+    g.finish();
+}
+
+criterion_group!(benches, single, pair);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Session clock driving the simulator: paced by wall-clock ticks when live,
+//! free-running on virtual time when backtesting.
+
+use std::time::{Duration, Instant};
+use tokio::time::{interval, Interval};
+
+pub struct Clock {
+    /// `None` in backtest mode: the next step is due immediately
+    pace:      Option<Interval>,
+    t0:        Instant,
+    tick_ms:   u64,
+    n:         u64,
+    max_ticks: Option<u64>,
+}
+
+impl Clock {
+    pub fn new(tick_ms: u64, backtest: bool, max_ticks: Option<u64>) -> Self {
+        Self {
+            pace: (!backtest).then(|| interval(Duration::from_millis(tick_ms))),
+            t0: Instant::now(),
+            tick_ms,
+            n: 0,
+            max_ticks,
+        }
+    }
+
+    /// Wait for the next step and return its session timestamp
+    /// (start + n·tick_ms), or `None` once the session is over.
+    pub async fn tick(&mut self) -> Option<Instant> {
+        if self.max_ticks.is_some_and(|m| self.n >= m) {
+            return None;
+        }
+        if let Some(pace) = &mut self.pace {
+            pace.tick().await;
+        }
+        self.n += 1;
+        Some(self.t0 + Duration::from_millis(self.tick_ms * self.n))
+    }
+
+    /// Session start; step `n` is stamped `start + n·tick_ms`.
+    pub fn start(&self) -> Instant { self.t0 }
+
+    /// Steps taken so far.
+    pub fn ticks(&self) -> u64 { self.n }
+}
@@ -0,0 +1,180 @@
+//! Strategy interface and the event loop that drives it against a venue.
+//!
+//! A venue and an [`Engine`] talk over the four channels created by
+//! [`link`]: market data and execution reports flow in, order requests flow
+//! out, and one `done` message per market-data event tells the venue the
+//! strategy has finished reacting (the lockstep that keeps seeded runs
+//! reproducible). Any strategy can therefore run on any simulator that
+//! speaks [`MarketData`].
+
+use crate::market::MarketData;
+use crate::order::{ExecReport, OrderReq};
+use crate::record::Recorder;
+use crate::report::Performance;
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// A trading strategy: reacts to events and answers with order intents.
+pub trait Strategy {
+    /// A market-data event; return the requests to send.
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq>;
+
+    /// An execution report for one of our orders.
+    fn on_execution(&mut self, _er: &ExecReport) -> Vec<OrderReq> { Vec::new() }
+
+    /// Periodic wake-up on session time, see [`Engine::timer`].
+    fn on_timer(&mut self, _now: Instant) -> Vec<OrderReq> { Vec::new() }
+
+    /// A request passed the risk gate and went out to the venue.
+    fn on_sent(&mut self, _req: &OrderReq) {}
+}
+
+/// The venue's ends of a [`link`].
+pub struct VenueLink {
+    pub md_tx:   mpsc::Sender<MarketData>,
+    pub er_tx:   mpsc::Sender<ExecReport>,
+    pub od_rx:   mpsc::Receiver<OrderReq>,
+    pub done_rx: mpsc::Receiver<()>,
+}
+
+/// The engine's ends of a [`link`].
+pub struct EngineLink {
+    md_rx:   mpsc::Receiver<MarketData>,
+    er_rx:   mpsc::Receiver<ExecReport>,
+    od_tx:   mpsc::Sender<OrderReq>,
+    done_tx: mpsc::Sender<()>,
+}
+
+/// Channels connecting one venue to one engine.
+pub fn link(capacity: usize) -> (VenueLink, EngineLink) {
+    let (md_tx, md_rx)     = mpsc::channel(capacity);
+    let (er_tx, er_rx)     = mpsc::channel(capacity);
+    let (od_tx, od_rx)     = mpsc::channel(capacity);
+    let (done_tx, done_rx) = mpsc::channel(capacity);
+    (
+        VenueLink { md_tx, er_tx, od_rx, done_rx },
+        EngineLink { md_rx, er_rx, od_tx, done_tx },
+    )
+}
+
+/// Pre-trade check: sees the strategy's state, the request and session time.
+pub type RiskGate<S> = Box<dyn FnMut(&S, &OrderReq, Instant) -> bool>;
+
+/// Drives a [`Strategy`] until the venue ends the session.
+pub struct Engine<S> {
+    strategy:   S,
+    link:       EngineLink,
+    risk:       Option<RiskGate<S>>,
+    timer:      Option<Duration>,
+    next_timer: Option<Instant>,
+    /// session time of the latest market-data event
+    now:        Option<Instant>,
+    recorder:   Option<Recorder>,
+    perf:       Option<Performance>,
+}
+
+impl<S: Strategy> Engine<S> {
+    pub fn new(strategy: S, link: EngineLink) -> Self {
+        Self { strategy, link, risk: None, timer: None, next_timer: None, now: None, recorder: None, perf: None }
+    }
+
+    /// Only send requests `gate` approves.
+    pub fn risk(mut self, gate: impl FnMut(&S, &OrderReq, Instant) -> bool + 'static) -> Self {
+        self.risk = Some(Box::new(gate));
+        self
+    }
+
+    /// Call [`Strategy::on_timer`] every `every` of session time. Timers
+    /// fire on the market-data clock, so they replay identically in backtests.
+    pub fn timer(mut self, every: Duration) -> Self {
+        self.timer = Some(every);
+        self
+    }
+
+    /// Write every market-data event and execution report to `recorder`.
+    pub fn record(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Score the session with `perf` and print its report when it ends.
+    pub fn report(mut self, perf: Performance) -> Self {
+        self.perf = Some(perf);
+        self
+    }
+
+    /// Run until the venue hangs up; hands the strategy back for reporting.
+    pub async fn run(mut self) -> S {
+        loop {
+            tokio::select! {
+                // the venue emits a step's reports before its market data: keep that order
+                biased;
+
+                Some(er) = self.link.er_rx.recv() => {
+                    let now = self.now.unwrap_or_else(Instant::now);
+                    self.write(|rec| rec.execution(&er, now));
+                    if let Some(perf) = &mut self.perf { perf.on_execution(&er); }
+                    let reqs = self.strategy.on_execution(&er);
+                    if !self.submit(reqs).await { break; }
+                }
+
+                Some(md) = self.link.md_rx.recv() => {
+                    let now = md.ts();
+                    self.now = Some(now);
+                    self.write(|rec| rec.market_data(&md));
+                    if let Some(perf) = &mut self.perf { perf.on_market_data(&md); }
+
+                    let mut reqs = self.fire_timers(now);
+                    reqs.extend(self.strategy.on_market_data(&md));
+                    if !self.submit(reqs).await { break; }
+
+                    // reaction complete: the venue may step again
+                    if self.link.done_tx.send(()).await.is_err() { break; }
+                }
+
+                else => break,
+            }
+        }
+        self.write(Recorder::flush);
+        if let Some(perf) = &self.perf { perf.finish(); }
+        self.strategy
+    }
+
+    /// A failed write stops the recording but not the session.
+    fn write(&mut self, f: impl FnOnce(&mut Recorder) -> io::Result<()>) {
+        if let Some(rec) = &mut self.recorder {
+            if let Err(e) = f(rec) {
+                warn!("recording stopped: {e}");
+                self.recorder = None;
+            }
+        }
+    }
+
+    fn fire_timers(&mut self, now: Instant) -> Vec<OrderReq> {
+        let Some(every) = self.timer else { return Vec::new() };
+        let next = self.next_timer.get_or_insert(now + every);
+        let mut reqs = Vec::new();
+        while *next <= now {
+            *next += every;
+            reqs.extend(self.strategy.on_timer(now));
+        }
+        reqs
+    }
+
+    /// Gate and send; `false` once the venue is gone.
+    async fn submit(&mut self, reqs: Vec<OrderReq>) -> bool {
+        let now = self.now.unwrap_or_else(Instant::now);
+        for req in reqs {
+            if let Some(gate) = &mut self.risk {
+                if !gate(&self.strategy, &req, now) { continue; }
+            }
+            self.strategy.on_sent(&req);
+            if self.link.od_tx.send(req).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,145 @@
+//! Trading costs.
+
+use crate::order::{Fill, Liquidity};
+use serde::Deserialize;
+
+/// Fee rates for one volume tier. Positive is a cost, negative a rebate
+/// (e.g. `maker_bps = -0.5` pays half a bp for adding liquidity). Basis
+/// points apply to notional and `*_per_unit` to quantity (per share or
+/// contract); a venue may charge both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct FeeRates {
+    #[serde(default)]
+    pub maker_bps: f64,
+    #[serde(default)]
+    pub taker_bps: f64,
+    #[serde(default)]
+    pub maker_per_unit: f64,
+    #[serde(default)]
+    pub taker_per_unit: f64,
+}
+
+impl FeeRates {
+    /// Fee charged on an execution of `qty` at `px`.
+    pub fn fee(&self, liquidity: Liquidity, px: f64, qty: f64) -> f64 {
+        let (bps, per_unit) = match liquidity {
+            Liquidity::Maker => (self.maker_bps, self.maker_per_unit),
+            Liquidity::Taker => (self.taker_bps, self.taker_per_unit),
+        };
+        px * qty * bps / 10_000.0 + qty * per_unit
+    }
+}
+
+/// Rates that apply once the session's traded notional on the venue has
+/// reached `volume`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FeeTier {
+    pub volume: f64,
+    #[serde(flatten)]
+    pub rates:  FeeRates,
+}
+
+/// A venue's fees: base rates, replaced by the highest volume tier reached.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FeeSchedule {
+    #[serde(flatten)]
+    pub base:  FeeRates,
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+}
+
+impl FeeSchedule {
+    /// Rates in force after `volume` of notional has traded.
+    pub fn rates(&self, volume: f64) -> FeeRates {
+        self.tiers.iter()
+            .filter(|t| volume >= t.volume)
+            .max_by(|a, b| a.volume.total_cmp(&b.volume))
+            .map_or(self.base, |t| t.rates)
+    }
+}
+
+/// A venue's fee account for the session: charges each fill at the tier
+/// its traded volume so far has earned.
+#[derive(Debug, Clone, Default)]
+pub struct FeeMeter {
+    schedule: FeeSchedule,
+    volume:   f64,
+}
+
+impl FeeMeter {
+    pub fn new(schedule: FeeSchedule) -> Self {
+        Self { schedule, volume: 0.0 }
+    }
+
+    /// Stamp `fill` with its fee and count its notional towards the tiers.
+    pub fn charge(&mut self, mut fill: Fill) -> Fill {
+        fill.fee = self.schedule.rates(self.volume).fee(fill.liquidity, fill.px, fill.qty);
+        self.volume += fill.px * fill.qty;
+        fill
+    }
+
+    /// Notional traded so far.
+    pub fn volume(&self) -> f64 { self.volume }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::Side;
+    use std::time::Instant;
+
+    fn fill(liquidity: Liquidity, px: f64, qty: f64) -> Fill {
+        Fill { id: 1, symbol: "X".into(), side: Side::Buy, px, qty, leaves: 0.0, liquidity, fee: 0.0, ts: Instant::now() }
+    }
+
+    fn rates(maker_bps: f64, taker_bps: f64) -> FeeRates {
+        FeeRates { maker_bps, taker_bps, ..FeeRates::default() }
+    }
+
+    fn tiered() -> FeeSchedule {
+        FeeSchedule {
+            base:  rates(-0.5, 2.0),
+            tiers: vec![
+                FeeTier { volume: 50_000.0, rates: rates(-1.5, 1.0) },
+                FeeTier { volume: 10_000.0, rates: rates(-1.0, 1.5) },
+            ],
+        }
+    }
+
+    #[test]
+    fn tier_applies_from_its_volume_on() {
+        let mut m = FeeMeter::new(tiered());
+        let f = m.charge(fill(Liquidity::Taker, 100.0, 99.0));
+        assert!((f.fee - 1.98).abs() < 1e-12);
+        // 9 900 traded, just below the tier: still the base 2 bps
+        let f = m.charge(fill(Liquidity::Taker, 100.0, 1.0));
+        assert!((f.fee - 0.02).abs() < 1e-12);
+        // 10 000 traded: 1.5 bps
+        assert_eq!(m.volume(), 10_000.0);
+        let f = m.charge(fill(Liquidity::Taker, 100.0, 1.0));
+        assert!((f.fee - 0.015).abs() < 1e-12);
+    }
+
+    #[test]
+    fn highest_tier_reached_wins_whatever_the_order() {
+        let s = tiered();
+        assert_eq!(s.rates(0.0), s.base);
+        assert_eq!(s.rates(49_999.0), rates(-1.0, 1.5));
+        assert_eq!(s.rates(1e9), rates(-1.5, 1.0));
+    }
+
+    #[test]
+    fn negative_maker_bps_is_a_rebate() {
+        let mut m = FeeMeter::new(tiered());
+        let f = m.charge(fill(Liquidity::Maker, 100.0, 10.0));
+        assert!((f.fee + 0.05).abs() < 1e-12, "{}", f.fee);
+    }
+
+    #[test]
+    fn bps_and_per_unit_fees_add_up() {
+        let r = FeeRates { taker_bps: 2.0, taker_per_unit: 0.01, maker_per_unit: -0.002, ..FeeRates::default() };
+        // 200 notional · 2 bps + 4 · 0.01
+        assert!((r.fee(Liquidity::Taker, 50.0, 4.0) - 0.08).abs() < 1e-12);
+        assert!((r.fee(Liquidity::Maker, 50.0, 4.0) + 0.008).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,312 @@
+//! Historical market data from CSV (and, with the `parquet` feature,
+//! Parquet) files, turned into the [`MarketData`] a simulator would publish.
+//!
+//! One row per event; columns are matched by header name and extra columns
+//! are ignored:
+//!
+//! | Column                         | Meaning                                        |
+//! | ------------------------------ | ---------------------------------------------- |
+//! | `ts` / `ts_us` / `ts_ms`       | event time in ns / µs / ms, any epoch          |
+//! | `symbol`                       | instrument; other symbols are skipped          |
+//! | `bid`, `ask`                   | top of book → [`Tick`]                         |
+//! | `price`                        | trade or index print → [`Tick::at`]            |
+//! | `bid_vol_0..4`, `ask_vol_0..4` | optional L2 sizes → a [`Book`] after the tick  |
+//!
+//! A row uses `bid`/`ask` when both are filled and falls back to `price`,
+//! so quote and trade files (or a mix of both) load the same way.
+
+use crate::market::{Book, MarketData, Tick, DEPTH};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Load the events for `symbols`, oldest first; the earliest event is
+/// stamped `origin` and the rest keep their recorded spacing.
+pub fn load(path: impl AsRef<Path>, symbols: &[&str], origin: Instant) -> io::Result<Vec<MarketData>> {
+    let path = path.as_ref();
+    let mut rows = match path.extension().and_then(|e| e.to_str()) {
+        Some("parquet") => read_parquet(path)?,
+        _ => read_csv(path)?,
+    };
+    rows.retain(|r| symbols.contains(&r.symbol.as_str()));
+    rows.sort_by_key(|r| r.ts_ns);
+
+    let t0 = rows.first().map_or(0, |r| r.ts_ns);
+    let mut events = Vec::with_capacity(rows.len());
+    for row in rows {
+        let ts = origin + Duration::from_nanos(row.ts_ns.saturating_sub(t0) as u64);
+        events.extend(row.into_events(ts));
+    }
+    Ok(events)
+}
+
+/// One file row, already reduced to the columns we understand.
+struct Row {
+    ts_ns:   i64,
+    symbol:  String,
+    bid:     Option<f64>,
+    ask:     Option<f64>,
+    price:   Option<f64>,
+    bid_vol: Option<[f64; DEPTH]>,
+    ask_vol: Option<[f64; DEPTH]>,
+}
+
+impl Row {
+    fn into_events(self, ts: Instant) -> Vec<MarketData> {
+        let tick = match (self.bid, self.ask, self.price) {
+            (Some(bid), Some(ask), _) => Tick { symbol: self.symbol, bid, ask, ts },
+            (_, _, Some(px))          => Tick::at(self.symbol, px, ts),
+            _                         => return Vec::new(),
+        };
+        let book = match (self.bid_vol, self.ask_vol) {
+            (Some(bid_vol), Some(ask_vol)) => Some(Book {
+                symbol: tick.symbol.clone(),
+                bid_vol,
+                ask_vol,
+                mid: tick.mid(),
+                ts,
+            }),
+            _ => None,
+        };
+        let mut events = vec![MarketData::Tick(tick)];
+        events.extend(book.map(MarketData::Book));
+        events
+    }
+}
+
+/// Column positions, resolved once from the header.
+struct Layout {
+    ts:      usize,
+    /// nanoseconds per unit of the `ts` column
+    ts_unit: i64,
+    symbol:  usize,
+    bid:     Option<usize>,
+    ask:     Option<usize>,
+    price:   Option<usize>,
+    depth:   Option<([usize; DEPTH], [usize; DEPTH])>,
+}
+
+impl Layout {
+    fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> io::Result<Self> {
+        let names: Vec<&str> = names.into_iter().map(str::trim).collect();
+        let find = |name: &str| names.iter().position(|n| *n == name);
+
+        let (ts, ts_unit) = [("ts", 1), ("ts_us", 1_000), ("ts_ms", 1_000_000)]
+            .into_iter()
+            .find_map(|(name, unit)| find(name).map(|i| (i, unit)))
+            .ok_or_else(|| invalid("missing ts / ts_us / ts_ms column"))?;
+        let symbol = find("symbol").ok_or_else(|| invalid("missing symbol column"))?;
+        let (bid, ask, price) = (find("bid"), find("ask"), find("price"));
+        if (bid.is_none() || ask.is_none()) && price.is_none() {
+            return Err(invalid("need bid and ask columns, or price"));
+        }
+
+        let levels = |side: &str| -> Option<[usize; DEPTH]> {
+            let cols: Vec<usize> = (0..DEPTH)
+                .map(|lvl| find(&format!("{side}_vol_{lvl}")))
+                .collect::<Option<_>>()?;
+            cols.try_into().ok()
+        };
+        let depth = levels("bid").zip(levels("ask"));
+
+        Ok(Self { ts, ts_unit, symbol, bid, ask, price, depth })
+    }
+
+    /// Pick our columns out of a row; `num` reads a numeric cell.
+    fn row(&self,
+           ts_ns: i64,
+           symbol: String,
+           num: impl Fn(usize) -> Option<f64>) -> Row
+    {
+        let vols = |cols: [usize; DEPTH]| -> Option<[f64; DEPTH]> {
+            let mut v = [0.0; DEPTH];
+            for (lvl, col) in cols.into_iter().enumerate() {
+                v[lvl] = num(col)?;
+            }
+            Some(v)
+        };
+        Row {
+            ts_ns,
+            symbol,
+            bid:     self.bid.and_then(&num),
+            ask:     self.ask.and_then(&num),
+            price:   self.price.and_then(&num),
+            bid_vol: self.depth.and_then(|(b, _)| vols(b)),
+            ask_vol: self.depth.and_then(|(_, a)| vols(a)),
+        }
+    }
+}
+
+fn read_csv(path: &Path) -> io::Result<Vec<Row>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let layout = Layout::new(rdr.headers()?.iter())?;
+
+    let mut rows = Vec::new();
+    for rec in rdr.records() {
+        let rec = rec?;
+        let cell = |i: usize| rec.get(i).map(str::trim).filter(|c| !c.is_empty());
+        let ts: i64 = cell(layout.ts)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| invalid(format!("bad timestamp on line {}", line(&rec))))?;
+        let ts_ns = ts
+            .checked_mul(layout.ts_unit)
+            .ok_or_else(|| invalid(format!("timestamp out of range on line {}", line(&rec))))?;
+        let symbol = cell(layout.symbol).unwrap_or_default().to_string();
+        rows.push(layout.row(ts_ns, symbol, |i| cell(i).and_then(|c| c.parse().ok())));
+    }
+    Ok(rows)
+}
+
+fn line(rec: &csv::StringRecord) -> u64 {
+    rec.position().map_or(0, |p| p.line())
+}
+
+#[cfg(feature = "parquet")]
+fn read_parquet(path: &Path) -> io::Result<Vec<Row>> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    let reader = SerializedFileReader::try_from(std::fs::File::open(path)?).map_err(io::Error::other)?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    let layout = Layout::new(schema.columns().iter().map(|c| c.name()))?;
+
+    let num = |f: &Field| match *f {
+        Field::Double(v) => Some(v),
+        Field::Float(v)  => Some(v as f64),
+        Field::Int(v)    => Some(v as f64),
+        Field::Long(v)   => Some(v as f64),
+        _ => None,
+    };
+
+    let mut rows = Vec::new();
+    for (n, row) in reader.into_iter().enumerate() {
+        let row = row.map_err(io::Error::other)?;
+        let fields: Vec<&Field> = row.get_column_iter().map(|(_, f)| f).collect();
+        // logical timestamp types carry their own unit
+        let ts_ns = match fields.get(layout.ts) {
+            Some(Field::TimestampMillis(v)) => v.checked_mul(1_000_000),
+            Some(Field::TimestampMicros(v)) => v.checked_mul(1_000),
+            Some(Field::Long(v))            => v.checked_mul(layout.ts_unit),
+            Some(Field::Int(v))             => (*v as i64).checked_mul(layout.ts_unit),
+            _ => None,
+        }
+        .ok_or_else(|| invalid(format!("bad timestamp in parquet row {n}")))?;
+        let symbol = match fields.get(layout.symbol) {
+            Some(Field::Str(s)) => s.clone(),
+            _ => String::new(),
+        };
+        rows.push(layout.row(ts_ns, symbol, |i| fields.get(i).and_then(|f| num(f))));
+    }
+    Ok(rows)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn read_parquet(path: &Path) -> io::Result<Vec<Row>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{}: built without the `parquet` feature", path.display()),
+    ))
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Write `csv` to a scratch file unique to this test.
+    fn fixture(name: &str, csv: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hft-history-{}-{name}.csv", std::process::id()));
+        std::fs::write(&path, csv).unwrap();
+        path
+    }
+
+    fn load_csv(name: &str, csv: &str, symbols: &[&str], origin: Instant) -> io::Result<Vec<MarketData>> {
+        let path = fixture(name, csv);
+        let out = load(&path, symbols, origin);
+        std::fs::remove_file(&path).ok();
+        out
+    }
+
+    fn ticks(events: &[MarketData]) -> Vec<&Tick> {
+        events.iter().filter_map(|e| match e { MarketData::Tick(t) => Some(t), _ => None }).collect()
+    }
+
+    #[test]
+    fn ns_quotes_are_sorted_filtered_and_rebased() {
+        let t0 = Instant::now();
+        let csv = "ts,symbol,bid,ask\n\
+                   3000,AAA,10.0,10.2\n\
+                   1000,AAA,9.9,10.1\n\
+                   2000,BBB,50.0,50.5\n";
+        let ev = load_csv("ns", csv, &["AAA"], t0).unwrap();
+        let t = ticks(&ev);
+        assert_eq!(ev.len(), 2);
+        assert!(t.iter().all(|t| t.symbol == "AAA"));
+        assert_eq!((t[0].bid, t[0].ask, t[0].ts), (9.9, 10.1, t0));
+        assert_eq!((t[1].bid, t[1].ask), (10.0, 10.2));
+        assert_eq!(t[1].ts - t0, Duration::from_nanos(2000));
+    }
+
+    #[test]
+    fn ms_prices_become_flat_ticks() {
+        let t0 = Instant::now();
+        let csv = "ts_ms,symbol,price\n100,IDX,4000.5\n350,IDX,4001.0\n";
+        let ev = load_csv("ms", csv, &["IDX"], t0).unwrap();
+        let t = ticks(&ev);
+        assert_eq!(t.len(), 2);
+        assert_eq!((t[0].bid, t[0].ask), (4000.5, 4000.5));
+        assert_eq!(t[1].ts - t0, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn us_rows_fall_back_to_price_without_a_full_quote() {
+        let t0 = Instant::now();
+        let csv = "ts_us,symbol,bid,ask,price\n\
+                   10,AAA,9.9,10.1,\n\
+                   15,AAA,,10.1,10.0\n\
+                   20,AAA,,,\n";
+        let ev = load_csv("us", csv, &["AAA"], t0).unwrap();
+        let t = ticks(&ev);
+        assert_eq!(t.len(), 2, "a row with nothing usable is dropped");
+        assert_eq!((t[0].bid, t[0].ask), (9.9, 10.1));
+        assert_eq!((t[1].bid, t[1].ask), (10.0, 10.0));
+        assert_eq!(t[1].ts - t0, Duration::from_micros(5));
+    }
+
+    #[test]
+    fn depth_columns_add_a_book_after_the_tick() {
+        let t0 = Instant::now();
+        let csv = "ts,symbol,bid,ask,\
+                   bid_vol_0,bid_vol_1,bid_vol_2,bid_vol_3,bid_vol_4,\
+                   ask_vol_0,ask_vol_1,ask_vol_2,ask_vol_3,ask_vol_4\n\
+                   1,AAA,9.9,10.1,1,2,3,4,5,6,7,8,9,10\n";
+        let ev = load_csv("depth", csv, &["AAA"], t0).unwrap();
+        assert_eq!(ev.len(), 2);
+        assert!(matches!(ev[0], MarketData::Tick(_)));
+        let MarketData::Book(b) = &ev[1] else { panic!("expected a book, got {:?}", ev[1]) };
+        assert_eq!(b.bid_vol, [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(b.ask_vol, [6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert!((b.mid - 10.0).abs() < 1e-12);
+        assert_eq!((b.symbol.as_str(), b.ts), ("AAA", t0));
+    }
+
+    #[test]
+    fn out_of_range_timestamp_names_the_line() {
+        let csv = "ts_ms,symbol,price\n1,AAA,1.0\n9223372036854775,AAA,1.0\n";
+        let err = load_csv("overflow", csv, &["AAA"], Instant::now()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 3"), "{err}");
+    }
+
+    #[test]
+    fn missing_columns_are_refused() {
+        let no_ts = load_csv("no-ts", "time,symbol,price\n1,AAA,1.0\n", &["AAA"], Instant::now());
+        assert!(no_ts.unwrap_err().to_string().contains("ts"));
+        let no_px = load_csv("no-px", "ts,symbol,bid\n1,AAA,1.0\n", &["AAA"], Instant::now());
+        assert!(no_px.unwrap_err().to_string().contains("price"));
+    }
+}
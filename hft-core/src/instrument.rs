@@ -0,0 +1,23 @@
+//! What we trade.
+
+/// Exchange symbol, e.g. `"BTC-USD"` or a currency pair such as `"EUR/USD"`.
+pub type Symbol = String;
+
+/// Static description of a tradeable instrument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instrument {
+    pub symbol:  Symbol,
+    /// minimum price increment
+    pub tick_sz: f64,
+}
+
+impl Instrument {
+    pub fn new(symbol: impl Into<Symbol>, tick_sz: f64) -> Self {
+        Self { symbol: symbol.into(), tick_sz }
+    }
+
+    /// Nearest price on the tick grid.
+    pub fn round_px(&self, px: f64) -> f64 {
+        (px / self.tick_sz).round() * self.tick_sz
+    }
+}
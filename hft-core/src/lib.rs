@@ -0,0 +1,33 @@
+//! Types shared by every strategy binary: instruments, orders, execution
+//! reports, market data, the session clock that drives the simulators, the
+//! engine that runs any strategy against any of them, session recording
+//! and replay, historical data loading, rolling-window statistics, and the
+//! end-of-run performance report.
+//!
+//! Anything that wants to work across strategies (recorders, risk,
+//! analytics) should speak these types rather than a crate's own.
+
+pub mod clock;
+pub mod engine;
+pub mod fees;
+pub mod history;
+pub mod instrument;
+pub mod market;
+pub mod order;
+pub mod position;
+pub mod record;
+pub mod replay;
+pub mod report;
+pub mod rolling;
+
+pub use clock::Clock;
+pub use engine::{link, Engine, EngineLink, RiskGate, Strategy, VenueLink};
+pub use fees::{FeeMeter, FeeRates, FeeSchedule, FeeTier};
+pub use instrument::{Instrument, Symbol};
+pub use market::{Book, MarketData, Tick, Trade, DEPTH};
+pub use order::{ExecReport, Fill, Liquidity, Order, OrderId, OrderIds, OrderReq, Side};
+pub use position::Position;
+pub use record::Recorder;
+pub use replay::Replay;
+pub use report::{Performance, Report};
+pub use rolling::{Rolling, RollingCov};
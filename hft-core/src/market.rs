@@ -0,0 +1,92 @@
+//! Market data published by a venue.
+//!
+//! Timestamps are session time from [`Clock`](crate::Clock), not wall time,
+//! so a backtest and a paced run stamp the same event identically.
+
+use crate::instrument::Symbol;
+use crate::order::Side;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Price levels carried by a [`Book`] snapshot.
+pub const DEPTH: usize = 5;
+
+/// Top of book for one instrument. Feeds that only publish a single price
+/// (a last trade or an index level) set `bid == ask`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tick {
+    pub symbol: Symbol,
+    pub bid:    f64,
+    pub ask:    f64,
+    /// carried by the record envelope on disk, see [`record`](crate::record)
+    #[serde(skip, default = "crate::record::unset_ts")]
+    pub ts:     Instant,
+}
+
+impl Tick {
+    /// A one-price tick (`bid == ask == px`).
+    pub fn at(symbol: impl Into<Symbol>, px: f64, ts: Instant) -> Self {
+        Self { symbol: symbol.into(), bid: px, ask: px, ts }
+    }
+
+    pub fn mid(&self) -> f64 { (self.bid + self.ask) / 2.0 }
+
+    pub fn spread(&self) -> f64 { self.ask - self.bid }
+}
+
+/// Depth snapshot: resting size on the first [`DEPTH`] levels each side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Book {
+    pub symbol:  Symbol,
+    pub bid_vol: [f64; DEPTH],   // level 0 = best
+    pub ask_vol: [f64; DEPTH],
+    pub mid:     f64,
+    #[serde(skip, default = "crate::record::unset_ts")]
+    pub ts:      Instant,
+}
+
+/// A print on the tape: `qty` changed hands at `px`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub symbol: Symbol,
+    pub px:     f64,
+    pub qty:    f64,
+    /// side of the aggressing (liquidity-taking) order
+    pub side:   Side,
+    #[serde(skip, default = "crate::record::unset_ts")]
+    pub ts:     Instant,
+}
+
+/// Anything a venue can publish on its market-data stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketData {
+    Tick(Tick),
+    Book(Book),
+    Trade(Trade),
+}
+
+impl MarketData {
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketData::Tick(t) => &t.symbol,
+            MarketData::Book(b) => &b.symbol,
+            MarketData::Trade(t) => &t.symbol,
+        }
+    }
+
+    pub fn ts(&self) -> Instant {
+        match self {
+            MarketData::Tick(t) => t.ts,
+            MarketData::Book(b) => b.ts,
+            MarketData::Trade(t) => t.ts,
+        }
+    }
+
+    pub fn set_ts(&mut self, ts: Instant) {
+        match self {
+            MarketData::Tick(t) => t.ts = ts,
+            MarketData::Book(b) => b.ts = ts,
+            MarketData::Trade(t) => t.ts = ts,
+        }
+    }
+}
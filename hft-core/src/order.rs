@@ -0,0 +1,133 @@
+//! Order entry (strategy → venue) and execution reports (venue → strategy).
+
+use crate::instrument::Symbol;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side { Buy, Sell }
+
+impl Side {
+    /// +1 for buys, −1 for sells: signed quantity is `side.sign() * qty`.
+    pub fn sign(self) -> f64 {
+        match self { Side::Buy => 1.0, Side::Sell => -1.0 }
+    }
+
+    pub fn opposite(self) -> Side {
+        match self { Side::Buy => Side::Sell, Side::Sell => Side::Buy }
+    }
+}
+
+/// Whether an execution added liquidity (our order was resting) or took it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Liquidity { Maker, Taker }
+
+/// client-assigned order identifier
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id:     OrderId,
+    pub symbol: Symbol,
+    pub side:   Side,
+    /// limit price; `None` takes whatever the venue offers
+    pub px:     Option<f64>,
+    pub qty:    f64,
+}
+
+impl Order {
+    pub fn limit(id: OrderId, symbol: impl Into<Symbol>, side: Side, px: f64, qty: f64) -> Self {
+        Self { id, symbol: symbol.into(), side, px: Some(px), qty }
+    }
+
+    pub fn market(id: OrderId, symbol: impl Into<Symbol>, side: Side, qty: f64) -> Self {
+        Self { id, symbol: symbol.into(), side, px: None, qty }
+    }
+}
+
+/// strategy → venue
+///
+/// `Replace` amends a resting limit order; market orders never rest, so a
+/// venue rejects cancels and amends for them as unknown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OrderReq {
+    New(Order),
+    Cancel  { id: OrderId },
+    Replace { id: OrderId, px: f64, qty: f64 },
+}
+
+impl OrderReq {
+    pub fn id(&self) -> OrderId {
+        match self {
+            OrderReq::New(o) => o.id,
+            OrderReq::Cancel { id } | OrderReq::Replace { id, .. } => *id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub id:     OrderId,
+    pub symbol: Symbol,
+    pub side:   Side,
+    pub px:     f64,
+    pub qty:    f64,
+    /// open quantity left after this fill (0 = done)
+    pub leaves: f64,
+    pub liquidity: Liquidity,
+    /// fee the venue charged, signed like [`FeeRates`](crate::FeeRates)
+    #[serde(default)]
+    pub fee:    f64,
+    /// session time of the execution
+    #[serde(skip, default = "crate::record::unset_ts")]
+    pub ts:     Instant,
+}
+
+/// venue → strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExecReport {
+    NewOrderAck { id: OrderId },
+    Reject      { id: OrderId, reason: String },
+    CancelAck   { id: OrderId },
+    ReplaceAck  { id: OrderId, px: f64, qty: f64 },
+    Fill(Fill),
+}
+
+impl ExecReport {
+    pub fn id(&self) -> OrderId {
+        match self {
+            ExecReport::NewOrderAck { id }
+            | ExecReport::Reject { id, .. }
+            | ExecReport::CancelAck { id }
+            | ExecReport::ReplaceAck { id, .. } => *id,
+            ExecReport::Fill(f) => f.id,
+        }
+    }
+}
+
+/// monotonically increasing order ids
+#[derive(Debug)]
+pub struct OrderIds {
+    next: OrderId,
+    step: OrderId,
+}
+
+impl Default for OrderIds {
+    fn default() -> Self { Self::strided(1, 1) }
+}
+
+impl OrderIds {
+    /// Ids `first`, `first + step`, `first + 2·step`, …: `step` generators
+    /// started at `1..=step` share one venue without colliding.
+    pub fn strided(first: OrderId, step: OrderId) -> Self {
+        assert!(first >= 1 && step >= 1, "order ids start at 1");
+        Self { next: first, step }
+    }
+
+    #[allow(clippy::should_implement_trait)]     // never exhausted; not an iterator
+    pub fn next(&mut self) -> OrderId {
+        let id = self.next;
+        self.next += self.step;
+        id
+    }
+}
@@ -0,0 +1,113 @@
+//! Average-cost position keeping for one instrument.
+
+use crate::order::Side;
+
+const QTY_EPS: f64 = 1e-9;
+
+/// Signed position with average entry price, realized PnL on closing
+/// trades, unrealized PnL against the latest mark, and fees paid.
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    qty:      f64,
+    avg_px:   f64,
+    realized: f64,
+    fees:     f64,
+    mark:     Option<f64>,
+}
+
+impl Position {
+    /// Apply an execution. `fee` is signed like [`FeeRates`](crate::FeeRates):
+    /// positive costs, negative rebates.
+    pub fn on_fill(&mut self, side: Side, qty: f64, px: f64, fee: f64) {
+        let signed = side.sign() * qty;
+        self.fees += fee;
+        if qty <= QTY_EPS {
+            return;
+        }
+
+        if self.qty.abs() <= QTY_EPS || self.qty.signum() == signed.signum() {
+            // opening or adding: blend the entry price
+            let open = self.qty.abs();
+            self.avg_px = (self.avg_px * open + px * qty) / (open + qty);
+            self.qty += signed;
+            return;
+        }
+
+        // reducing, closing or flipping
+        let closed = qty.min(self.qty.abs());
+        self.realized += closed * (px - self.avg_px) * self.qty.signum();
+        self.qty += signed;
+        if self.qty.abs() <= QTY_EPS {
+            self.qty = 0.0;
+            self.avg_px = 0.0;
+        } else if qty > closed {
+            // flipped through flat: the remainder opened at `px`
+            self.avg_px = px;
+        }
+    }
+
+    /// Mark the open position to `px` (typically the latest mid).
+    pub fn mark(&mut self, px: f64) { self.mark = Some(px); }
+
+    pub fn qty(&self) -> f64 { self.qty }
+    pub fn avg_px(&self) -> f64 { self.avg_px }
+    pub fn realized(&self) -> f64 { self.realized }
+    pub fn fees(&self) -> f64 { self.fees }
+    pub fn last_mark(&self) -> Option<f64> { self.mark }
+
+    /// PnL of the open position at the last mark (0 before the first mark).
+    pub fn unrealized(&self) -> f64 {
+        self.mark.map_or(0.0, |m| self.qty * (m - self.avg_px))
+    }
+
+    /// Realized + unrealized − fees.
+    pub fn net(&self) -> f64 { self.realized + self.unrealized() - self.fees }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool { (a - b).abs() < 1e-9 }
+
+    #[test]
+    fn adding_blends_the_entry_and_reducing_realizes() {
+        let mut p = Position::default();
+        p.on_fill(Side::Buy, 2.0, 100.0, 0.1);
+        p.on_fill(Side::Buy, 1.0, 103.0, 0.1);
+        assert!(close(p.qty(), 3.0) && close(p.avg_px(), 101.0));
+
+        p.on_fill(Side::Sell, 1.0, 105.0, 0.1);
+        assert!(close(p.qty(), 2.0) && close(p.avg_px(), 101.0));
+        assert!(close(p.realized(), 4.0));
+
+        p.mark(100.0);
+        assert!(close(p.unrealized(), -2.0));
+        assert!(close(p.net(), 4.0 - 2.0 - 0.3));
+    }
+
+    #[test]
+    fn flipping_through_flat_reopens_at_the_fill_price() {
+        let mut p = Position::default();
+        p.on_fill(Side::Sell, 2.0, 50.0, 0.0);
+        p.on_fill(Side::Buy, 5.0, 48.0, 0.0);
+        assert!(close(p.realized(), 4.0), "short 2 covered 2 lower");
+        assert!(close(p.qty(), 3.0) && close(p.avg_px(), 48.0));
+
+        p.on_fill(Side::Sell, 3.0, 47.0, -0.05);
+        assert_eq!((p.qty(), p.avg_px()), (0.0, 0.0));
+        assert!(close(p.realized(), 1.0));
+        assert!(close(p.fees(), -0.05), "rebates count as negative fees");
+        assert!(close(p.net(), 1.05));
+    }
+
+    #[test]
+    fn empty_fills_leave_the_position_alone() {
+        let mut p = Position::default();
+        p.on_fill(Side::Buy, 0.0, 100.0, 0.0);
+        assert_eq!((p.qty(), p.avg_px(), p.realized()), (0.0, 0.0, 0.0));
+        p.on_fill(Side::Buy, 1.0, 100.0, 0.0);
+        p.on_fill(Side::Sell, 0.0, 90.0, 0.0);
+        assert_eq!((p.qty(), p.avg_px(), p.realized()), (1.0, 100.0, 0.0));
+    }
+}
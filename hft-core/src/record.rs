@@ -0,0 +1,91 @@
+//! Session recording: every market-data event and execution report an
+//! [`Engine`](crate::Engine) sees, one JSON object per line.
+//!
+//! ```text
+//! {"t_ns":50000000,"md":{"Tick":{"symbol":"SIMPERP","bid":99.5,"ask":100.5}}}
+//! {"t_ns":50000000,"exec":{"Fill":{"id":3,"symbol":"SIMPERP",...}}}
+//! ```
+//!
+//! `t_ns` is nanoseconds of session time since the recording's origin
+//! (normally [`Clock::start`](crate::Clock::start)); in-memory `Instant`
+//! stamps are rebuilt from it when a file is read back.
+
+use crate::market::MarketData;
+use crate::order::ExecReport;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// One line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub t_ns:  u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    Md(MarketData),
+    Exec(ExecReport),
+}
+
+/// Placeholder for timestamps that are not stored inline; readers overwrite
+/// it from the record's `t_ns`.
+pub(crate) fn unset_ts() -> Instant { Instant::now() }
+
+/// Appends records to a JSONL file.
+pub struct Recorder {
+    out:    BufWriter<File>,
+    origin: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>, origin: Instant) -> io::Result<Self> {
+        Ok(Self { out: BufWriter::new(File::create(path)?), origin })
+    }
+
+    pub fn market_data(&mut self, md: &MarketData) -> io::Result<()> {
+        self.write(md.ts(), Event::Md(md.clone()))
+    }
+
+    /// Execution reports without their own timestamp are stamped `now`.
+    pub fn execution(&mut self, er: &ExecReport, now: Instant) -> io::Result<()> {
+        let ts = match er {
+            ExecReport::Fill(f) => f.ts,
+            _ => now,
+        };
+        self.write(ts, Event::Exec(er.clone()))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> { self.out.flush() }
+
+    fn write(&mut self, ts: Instant, event: Event) -> io::Result<()> {
+        let t_ns = ts.saturating_duration_since(self.origin).as_nanos() as u64;
+        serde_json::to_writer(&mut self.out, &Record { t_ns, event })?;
+        self.out.write_all(b"\n")
+    }
+}
+
+/// Read a recording back, re-stamping events as `origin + t_ns`.
+pub fn read(path: impl AsRef<Path>, origin: Instant) -> io::Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut rec: Record = serde_json::from_str(&line)?;
+        let ts = origin + Duration::from_nanos(rec.t_ns);
+        match &mut rec.event {
+            Event::Md(md) => md.set_ts(ts),
+            Event::Exec(ExecReport::Fill(f)) => f.ts = ts,
+            Event::Exec(_) => {}
+        }
+        records.push(rec);
+    }
+    Ok(records)
+}
@@ -0,0 +1,263 @@
+//! Venue that replays a recorded session (or historical data, see
+//! [`history`](crate::history)) in place of a simulator.
+//!
+//! Market data is published exactly as recorded, in lockstep with the
+//! engine and as fast as it can react. Orders are filled against the
+//! recorded touch: marketable orders at the opposite side, resting limits
+//! once the touch trades through their price — strictly, since a touch
+//! merely at the limit says nothing of our place in the queue. There is
+//! no queue or size
+//! modelling, so every fill is for the full open quantity, charged at the
+//! schedule given to [`Replay::fees`].
+
+use crate::fees::{FeeMeter, FeeSchedule};
+use crate::market::MarketData;
+use crate::order::{ExecReport, Fill, Liquidity, Order, OrderId, OrderReq, Side};
+use crate::record::{self, Event};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy)]
+struct Touch {
+    bid: f64,
+    ask: f64,
+    /// set from a top-of-book tick; depth-only mids never override it
+    from_tick: bool,
+}
+
+pub struct Replay {
+    md_tx:   mpsc::Sender<MarketData>,
+    er_tx:   mpsc::Sender<ExecReport>,
+    events:  Vec<MarketData>,
+    touch:   HashMap<String, Touch>,
+    /// resting limit orders, oldest first
+    resting: BTreeMap<OrderId, Order>,
+    /// session time of the event being replayed, stamped on fills
+    now:     Instant,
+    fees:    FeeMeter,
+}
+
+impl Replay {
+    /// Load the market data of a recording; its executions are ignored,
+    /// since the strategy under test produces its own.
+    pub fn open(path: impl AsRef<Path>,
+                md_tx: mpsc::Sender<MarketData>,
+                er_tx: mpsc::Sender<ExecReport>) -> io::Result<Self>
+    {
+        let events = record::read(path, Instant::now())?
+            .into_iter()
+            .filter_map(|r| match r.event {
+                Event::Md(md) => Some(md),
+                Event::Exec(_) => None,
+            })
+            .collect();
+        Ok(Self::new(events, md_tx, er_tx))
+    }
+
+    /// Replay `events`, which must be in time order.
+    pub fn new(events: Vec<MarketData>,
+               md_tx: mpsc::Sender<MarketData>,
+               er_tx: mpsc::Sender<ExecReport>) -> Self
+    {
+        let now = events.first().map_or_else(Instant::now, MarketData::ts);
+        Self {
+            md_tx,
+            er_tx,
+            events,
+            touch: HashMap::new(),
+            resting: BTreeMap::new(),
+            now,
+            fees: FeeMeter::default(),
+        }
+    }
+
+    /// Charge fills with `fees` (none by default).
+    pub fn fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = FeeMeter::new(fees);
+        self
+    }
+
+    /// Runs until the recording ends or the strategy hangs up; `done_rx`
+    /// carries one message per event once the strategy has reacted.
+    pub async fn run(mut self,
+                     mut od_rx: mpsc::Receiver<OrderReq>,
+                     mut done_rx: mpsc::Receiver<()>)
+    {
+        let events = std::mem::take(&mut self.events);
+        let n = events.len();
+        for md in events {
+            self.now = md.ts();
+            self.update_touch(&md);
+            let fills = self.cross_resting(md.symbol());
+            if self.publish(fills).await.is_err() || self.md_tx.send(md).await.is_err() {
+                break;
+            }
+
+            if done_rx.recv().await.is_none() {
+                break;
+            }
+            while let Ok(req) = od_rx.try_recv() {
+                let reports = self.on_request(req);
+                if self.publish(reports).await.is_err() {
+                    return;
+                }
+            }
+        }
+        info!("replay over after {n} events");
+    }
+
+    fn update_touch(&mut self, md: &MarketData) {
+        match md {
+            MarketData::Tick(t) => {
+                self.touch.insert(t.symbol.clone(), Touch { bid: t.bid, ask: t.ask, from_tick: true });
+            }
+            MarketData::Book(b) => {
+                let touch = self.touch.entry(b.symbol.clone())
+                    .or_insert(Touch { bid: b.mid, ask: b.mid, from_tick: false });
+                if !touch.from_tick {
+                    touch.bid = b.mid;
+                    touch.ask = b.mid;
+                }
+            }
+            // prints don't move the touch
+            MarketData::Trade(_) => {}
+        }
+    }
+
+    fn on_request(&mut self, req: OrderReq) -> Vec<ExecReport> {
+        match req {
+            OrderReq::New(order) => {
+                if order.qty <= 0.0 {
+                    return vec![reject(order.id, "non-positive qty")];
+                }
+                if self.resting.contains_key(&order.id) {
+                    return vec![reject(order.id, "duplicate order id")];
+                }
+                let Some(&touch) = self.touch.get(&order.symbol) else {
+                    return vec![reject(order.id, "no market data for symbol")];
+                };
+                let mut reports = vec![ExecReport::NewOrderAck { id: order.id }];
+                reports.extend(self.enter(order, touch));
+                reports
+            }
+
+            OrderReq::Cancel { id } => match self.resting.remove(&id) {
+                Some(_) => vec![ExecReport::CancelAck { id }],
+                None    => vec![reject(id, "unknown or closed order")],
+            },
+
+            OrderReq::Replace { id, px, qty } => {
+                let Some(mut order) = self.resting.remove(&id) else {
+                    return vec![reject(id, "unknown or closed order")];
+                };
+                if qty <= 0.0 {
+                    self.resting.insert(id, order);
+                    return vec![reject(id, "non-positive qty")];
+                }
+                order.px = Some(px);
+                order.qty = qty;
+                let touch = self.touch[&order.symbol];
+                let mut reports = vec![ExecReport::ReplaceAck { id, px, qty }];
+                reports.extend(self.enter(order, touch));
+                reports
+            }
+        }
+    }
+
+    /// Take the touch if marketable, otherwise rest.
+    fn enter(&mut self, order: Order, touch: Touch) -> Option<ExecReport> {
+        let far = match order.side { Side::Buy => touch.ask, Side::Sell => touch.bid };
+        let marketable = match (order.px, order.side) {
+            (None, _)             => true,
+            (Some(px), Side::Buy)  => px >= far,
+            (Some(px), Side::Sell) => px <= far,
+        };
+        if marketable {
+            return Some(ExecReport::Fill(self.fees.charge(fill(&order, far, Liquidity::Taker, self.now))));
+        }
+        self.resting.insert(order.id, order);
+        None
+    }
+
+    /// Resting orders the new touch has traded through fill at their limit.
+    fn cross_resting(&mut self, symbol: &str) -> Vec<ExecReport> {
+        let Some(&touch) = self.touch.get(symbol) else { return Vec::new() };
+        let crossed: Vec<OrderId> = self.resting.values()
+            .filter(|o| o.symbol == symbol)
+            .filter(|o| match (o.side, o.px) {
+                (Side::Buy,  Some(px)) => touch.ask < px,
+                (Side::Sell, Some(px)) => touch.bid > px,
+                (_, None)              => true,
+            })
+            .map(|o| o.id)
+            .collect();
+
+        let now = self.now;
+        crossed.into_iter()
+            .filter_map(|id| self.resting.remove(&id))
+            .map(|o| ExecReport::Fill(self.fees.charge(fill(&o, o.px.unwrap_or(touch.bid), Liquidity::Maker, now))))
+            .collect()
+    }
+
+    async fn publish(&self, reports: Vec<ExecReport>) -> Result<(), mpsc::error::SendError<ExecReport>> {
+        for r in reports {
+            self.er_tx.send(r).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Full fill of the open quantity, before fees.
+fn fill(order: &Order, px: f64, liquidity: Liquidity, ts: Instant) -> Fill {
+    Fill {
+        id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side,
+        px,
+        qty: order.qty,
+        leaves: 0.0,
+        liquidity,
+        fee: 0.0,
+        ts,
+    }
+}
+
+fn reject(id: OrderId, reason: &str) -> ExecReport {
+    ExecReport::Reject { id, reason: reason.into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::Tick;
+
+    fn tick(bid: f64, ask: f64) -> MarketData {
+        MarketData::Tick(Tick { symbol: "XYZ".into(), bid, ask, ts: Instant::now() })
+    }
+
+    #[test]
+    fn resting_limits_fill_only_once_the_touch_trades_through() {
+        let (md_tx, _md_rx) = mpsc::channel(1);
+        let (er_tx, _er_rx) = mpsc::channel(1);
+        let mut rp = Replay::new(Vec::new(), md_tx, er_tx);
+        let step = |rp: &mut Replay, md: MarketData| {
+            rp.update_touch(&md);
+            rp.cross_resting(md.symbol())
+        };
+
+        assert!(step(&mut rp, tick(99.9, 100.1)).is_empty());
+        let acks = rp.on_request(OrderReq::New(Order::limit(1, "XYZ", Side::Buy, 99.5, 2.0)));
+        assert!(matches!(&acks[..], [ExecReport::NewOrderAck { id: 1 }]));
+
+        // the ask reaching our bid is not enough
+        assert!(step(&mut rp, tick(99.4, 99.5)).is_empty());
+        let fills = step(&mut rp, tick(99.3, 99.4));
+        assert!(matches!(&fills[..], [ExecReport::Fill(f)]
+            if f.id == 1 && f.px == 99.5 && f.qty == 2.0 && f.liquidity == Liquidity::Maker));
+        assert!(rp.resting.is_empty());
+    }
+}
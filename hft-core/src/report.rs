@@ -0,0 +1,356 @@
+//! End-of-run performance report. The [`Engine`](crate::Engine) feeds it the
+//! same market data and fills the strategy sees, so every crate is scored
+//! the same way and the JSON outputs compare side by side.
+//!
+//! Equity is marked to the latest mid of each instrument. Sharpe and
+//! Sortino are computed on equity changes over fixed buckets of session
+//! time and are not annualised: compare runs with the same bucket.
+//! Totals add up each instrument's PnL in its own quote currency, so for
+//! cross-currency books (FX triangles) read the per-instrument rows.
+
+use crate::instrument::Symbol;
+use crate::market::MarketData;
+use crate::order::{ExecReport, Fill};
+use crate::position::Position;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Collects a session's fills and marks; see [`Performance::report`].
+pub struct Performance {
+    label:       String,
+    bucket:      Duration,
+    json:        Option<PathBuf>,
+    legs:        BTreeMap<Symbol, Leg>,
+    start:       Option<Instant>,
+    now:         Option<Instant>,
+    /// end of the current bucket and the equity it opened with
+    bucket_end:  Option<Instant>,
+    bucket_open: f64,
+    returns:     Vec<f64>,
+    peak:        f64,
+    max_dd:      f64,
+}
+
+/// Per-instrument state.
+#[derive(Default)]
+struct Leg {
+    pos:      Position,
+    fills:    u64,
+    turnover: f64,
+    /// open round trip: when it opened and realized − fees at that point
+    open:     Option<(Instant, f64)>,
+    trips:    u64,
+    wins:     u64,
+    held:     Duration,
+}
+
+impl Leg {
+    fn on_fill(&mut self, f: &Fill) {
+        let before = self.pos.qty();
+        let base = self.pos.realized() - self.pos.fees();
+        self.pos.on_fill(f.side, f.qty, f.px, f.fee);
+        self.fills += 1;
+        self.turnover += f.px * f.qty;
+
+        let after = self.pos.qty();
+        if before != 0.0 && (after == 0.0 || before.signum() != after.signum()) {
+            // flat again (or flipped through flat): the trip is over
+            if let Some((opened, base)) = self.open.take() {
+                self.trips += 1;
+                self.wins += u64::from(self.pos.realized() - self.pos.fees() > base);
+                self.held += f.ts.saturating_duration_since(opened);
+            }
+        }
+        if after != 0.0 && self.open.is_none() {
+            let base = if before == 0.0 { base } else { self.pos.realized() - self.pos.fees() };
+            self.open = Some((f.ts, base));
+        }
+    }
+}
+
+impl Performance {
+    /// `label` names the run in the report; `bucket` is the Sharpe/Sortino
+    /// sampling period, and must not be zero.
+    pub fn new(label: impl Into<String>, bucket: Duration) -> Self {
+        assert!(bucket > Duration::ZERO, "report bucket must be positive");
+        Self {
+            label: label.into(),
+            bucket,
+            json: None,
+            legs: BTreeMap::new(),
+            start: None,
+            now: None,
+            bucket_end: None,
+            bucket_open: 0.0,
+            returns: Vec::new(),
+            peak: 0.0,
+            max_dd: 0.0,
+        }
+    }
+
+    /// Also write the report to `path` as JSON.
+    pub fn json(mut self, path: impl Into<PathBuf>) -> Self {
+        self.json = Some(path.into());
+        self
+    }
+
+    pub fn on_market_data(&mut self, md: &MarketData) {
+        let mid = match md {
+            MarketData::Tick(t) => Some(t.mid()),
+            MarketData::Book(b) => Some(b.mid),
+            MarketData::Trade(_) => None,
+        };
+        if let Some(mid) = mid {
+            self.legs.entry(md.symbol().to_string()).or_default().pos.mark(mid);
+        }
+        self.advance(md.ts());
+    }
+
+    pub fn on_execution(&mut self, er: &ExecReport) {
+        if let ExecReport::Fill(f) = er {
+            self.legs.entry(f.symbol.clone()).or_default().on_fill(f);
+            self.advance(f.ts);
+        }
+    }
+
+    fn equity(&self) -> f64 {
+        self.legs.values().map(|l| l.pos.net()).sum()
+    }
+
+    /// Close any buckets that ended before `now` and update the drawdown.
+    fn advance(&mut self, now: Instant) {
+        let equity = self.equity();
+        self.start.get_or_insert(now);
+        self.now = Some(now);
+        let end = self.bucket_end.get_or_insert(now + self.bucket);
+        while *end <= now {
+            self.returns.push(equity - self.bucket_open);
+            self.bucket_open = equity;
+            *end += self.bucket;
+        }
+        self.peak = self.peak.max(equity);
+        self.max_dd = self.max_dd.max(self.peak - equity);
+    }
+
+    pub fn report(&self) -> Report {
+        let instruments: BTreeMap<Symbol, InstrumentReport> = self.legs.iter()
+            .map(|(sym, l)| (sym.clone(), InstrumentReport {
+                pnl:         l.pos.net(),
+                realized:    l.pos.realized(),
+                unrealized:  l.pos.unrealized(),
+                fees:        l.pos.fees(),
+                position:    l.pos.qty(),
+                fills:       l.fills,
+                turnover:    l.turnover,
+                round_trips: l.trips,
+                win_rate:    ratio(l.wins as f64, l.trips as f64),
+            }))
+            .collect();
+        let sum = |f: fn(&InstrumentReport) -> f64| instruments.values().map(f).sum::<f64>();
+        let trips: u64 = self.legs.values().map(|l| l.trips).sum();
+        let wins: u64 = self.legs.values().map(|l| l.wins).sum();
+        let held: Duration = self.legs.values().map(|l| l.held).sum();
+
+        Report {
+            label:         self.label.clone(),
+            duration_secs: match (self.start, self.now) {
+                (Some(s), Some(n)) => n.saturating_duration_since(s).as_secs_f64(),
+                _ => 0.0,
+            },
+            bucket_secs:   self.bucket.as_secs_f64(),
+            pnl:           sum(|i| i.pnl),
+            realized:      sum(|i| i.realized),
+            unrealized:    sum(|i| i.unrealized),
+            fees:          sum(|i| i.fees),
+            sharpe:        sharpe(&self.returns),
+            sortino:       sortino(&self.returns),
+            max_drawdown:  self.max_dd,
+            fills:         instruments.values().map(|i| i.fills).sum(),
+            turnover:      sum(|i| i.turnover),
+            round_trips:   trips,
+            win_rate:      ratio(wins as f64, trips as f64),
+            avg_hold_secs: ratio(held.as_secs_f64(), trips as f64),
+            instruments,
+        }
+    }
+
+    /// Print the report to stdout and write the JSON file, if any.
+    pub fn finish(&self) {
+        let report = self.report();
+        println!("{report}");
+        if let Some(path) = &self.json {
+            if let Err(e) = report.write_json(path) {
+                warn!("report not written to {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Whole-run summary; see the [module docs](self) for the definitions.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub label:         String,
+    pub duration_secs: f64,
+    pub bucket_secs:   f64,
+    pub pnl:           f64,
+    pub realized:      f64,
+    pub unrealized:    f64,
+    pub fees:          f64,
+    pub sharpe:        f64,
+    pub sortino:       f64,
+    pub max_drawdown:  f64,
+    pub fills:         u64,
+    /// traded notional
+    pub turnover:      f64,
+    /// flat → position → flat (or flipped) cycles
+    pub round_trips:   u64,
+    /// share of round trips that made money after fees
+    pub win_rate:      f64,
+    pub avg_hold_secs: f64,
+    pub instruments:   BTreeMap<Symbol, InstrumentReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstrumentReport {
+    pub pnl:         f64,
+    pub realized:    f64,
+    pub unrealized:  f64,
+    pub fees:        f64,
+    pub position:    f64,
+    pub fills:       u64,
+    pub turnover:    f64,
+    pub round_trips: u64,
+    pub win_rate:    f64,
+}
+
+impl Report {
+    pub fn write_json(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, self)?;
+        out.write_all(b"\n")?;
+        out.flush()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "==== {} — {:.1}s session ====", self.label, self.duration_secs)?;
+        writeln!(f, "pnl {:.2}  (realized {:.2}  unrealized {:.2}  fees {:.2})",
+                 self.pnl, self.realized, self.unrealized, self.fees)?;
+        writeln!(f, "sharpe {:.3}  sortino {:.3}  per {}s bucket   max drawdown {:.2}",
+                 self.sharpe, self.sortino, self.bucket_secs, self.max_drawdown)?;
+        writeln!(f, "fills {}  turnover {:.2}  round trips {}  win rate {:.1}%  avg hold {:.2}s",
+                 self.fills, self.turnover, self.round_trips, self.win_rate * 100.0, self.avg_hold_secs)?;
+        writeln!(f, "{:<12} {:>12} {:>12} {:>12} {:>10} {:>12} {:>6} {:>14}",
+                 "symbol", "pnl", "realized", "unrealized", "fees", "position", "fills", "turnover")?;
+        for (sym, i) in &self.instruments {
+            writeln!(f, "{:<12} {:>12.2} {:>12.2} {:>12.2} {:>10.2} {:>12.2} {:>6} {:>14.2}",
+                     sym, i.pnl, i.realized, i.unrealized, i.fees, i.position, i.fills, i.turnover)?;
+        }
+        Ok(())
+    }
+}
+
+fn ratio(num: f64, den: f64) -> f64 {
+    if den > 0.0 { num / den } else { 0.0 }
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    ratio(xs.iter().sum(), xs.len() as f64)
+}
+
+/// Mean over sample standard deviation of the bucket returns.
+fn sharpe(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(returns);
+    let var = returns.iter().map(|r| (r - m).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    ratio(m, var.sqrt())
+}
+
+/// Mean over downside deviation (losses only, target 0).
+fn sortino(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let down = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64;
+    ratio(mean(returns), down.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::Tick;
+    use crate::order::{Liquidity, Side};
+
+    fn close(a: f64, b: f64) -> bool { (a - b).abs() < 1e-9 }
+
+    #[test]
+    fn ratios_on_hand_checked_returns() {
+        // mean 1, sample std 2; downside deviation √(1/3)
+        let r = [1.0, -1.0, 3.0];
+        assert!(close(sharpe(&r), 0.5));
+        assert!(close(sortino(&r), 3f64.sqrt()));
+    }
+
+    #[test]
+    fn too_few_or_flat_returns_score_zero() {
+        for r in [&[][..], &[2.0], &[1.0, 1.0, 1.0]] {
+            assert_eq!(sharpe(r), 0.0, "{r:?}");
+        }
+        // no losing bucket: no downside to divide by
+        assert_eq!(sortino(&[1.0, 2.0]), 0.0);
+
+        let rep = Performance::new("empty", Duration::from_secs(1)).report();
+        for x in [rep.pnl, rep.sharpe, rep.sortino, rep.max_drawdown, rep.win_rate, rep.avg_hold_secs, rep.duration_secs] {
+            assert_eq!(x, 0.0);
+        }
+        assert_eq!((rep.fills, rep.round_trips), (0, 0));
+    }
+
+    #[test]
+    fn session_buckets_drawdown_and_round_trips() {
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+        let tick = |ms, mid: f64| MarketData::Tick(Tick { symbol: "XYZ".into(), bid: mid, ask: mid, ts: at(ms) });
+        let fill = |ms, side, px| ExecReport::Fill(Fill {
+            id: 1, symbol: "XYZ".into(), side, px, qty: 1.0, leaves: 0.0,
+            liquidity: Liquidity::Taker, fee: 0.0, ts: at(ms),
+        });
+
+        let mut perf = Performance::new("test", Duration::from_secs(1));
+        perf.on_market_data(&tick(0, 100.0));
+        perf.on_execution(&fill(500, Side::Buy, 100.0));
+        perf.on_market_data(&tick(1_000, 102.0));       // equity +2
+        perf.on_market_data(&tick(2_000, 99.0));        // equity −1: 3 off the peak
+        perf.on_execution(&fill(2_500, Side::Sell, 99.0));  // losing trip, held 2 s
+        perf.on_market_data(&tick(3_000, 99.0));
+        perf.on_execution(&fill(3_200, Side::Sell, 99.0));
+        perf.on_execution(&fill(3_500, Side::Buy, 98.0));   // winning trip, held 0.3 s
+        perf.on_market_data(&tick(4_000, 98.0));
+
+        assert_eq!(perf.returns, vec![2.0, -3.0, 0.0, 1.0]);
+        let rep = perf.report();
+        assert!(close(rep.max_drawdown, 3.0));
+        assert!(close(rep.pnl, 0.0) && close(rep.realized, 0.0));
+        assert_eq!((rep.fills, rep.round_trips), (4, 2));
+        assert!(close(rep.win_rate, 0.5));
+        assert!(close(rep.avg_hold_secs, 1.15));
+        assert!(close(rep.turnover, 100.0 + 99.0 + 99.0 + 98.0));
+        assert!(close(rep.duration_secs, 4.0));
+        assert!(close(rep.sharpe, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "report bucket must be positive")]
+    fn zero_bucket_is_refused() {
+        Performance::new("zero", Duration::ZERO);
+    }
+}
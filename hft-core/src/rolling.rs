@@ -0,0 +1,254 @@
+//! Fixed-length rolling windows with amortised O(1) statistics per update.
+//!
+//! Samples live in a ring buffer, and the mean and co-moments are updated
+//! Welford-style as a sample enters and the oldest one leaves, so a push
+//! costs the same whatever the window length. Once per window length the
+//! statistics are recomputed from the buffer, which keeps rounding from
+//! piling up over a long session: that push costs O(window length), so
+//! the cost is O(1) averaged over pushes, not on every one.
+//!
+//! All (co)variances are population ones (divided by `len`), matching
+//! `ndarray`'s `var(0.0)`.
+
+/// A rolling window over several series sampled together, keeping every
+/// series' mean and every pair's covariance.
+#[derive(Debug, Clone)]
+pub struct RollingCov {
+    series:   usize,
+    capacity: usize,
+    /// row-major samples, `series` values per row
+    buf:      Vec<f64>,
+    /// row the next sample goes into
+    head:     usize,
+    len:      usize,
+    mean:     Vec<f64>,
+    /// co-moments Σ (x_i − mean_i)(x_j − mean_j), `series × series`
+    co:       Vec<f64>,
+    /// pushes since the statistics were last recomputed from the buffer
+    pushes:   usize,
+    /// deviations from the mean of the row being added or removed
+    dev:      Vec<f64>,
+}
+
+impl RollingCov {
+    /// Window of the last `capacity` rows of `series` values.
+    pub fn new(series: usize, capacity: usize) -> Self {
+        assert!(series >= 1 && capacity >= 1, "rolling window needs a series and a capacity");
+        Self {
+            series,
+            capacity,
+            buf:    vec![0.0; series * capacity],
+            head:   0,
+            len:    0,
+            mean:   vec![0.0; series],
+            co:     vec![0.0; series * series],
+            pushes: 0,
+            dev:    vec![0.0; series],
+        }
+    }
+
+    /// Add a row, dropping the oldest one once the window is full. Every
+    /// `capacity`-th push also recomputes the statistics from the buffer.
+    pub fn push(&mut self, row: &[f64]) {
+        assert_eq!(row.len(), self.series, "row length must match the series count");
+        let n = self.series;
+        // the oldest row sits where the new one goes
+        if self.len == self.capacity {
+            self.remove(self.head * n);
+        }
+        self.buf[self.head * n..(self.head + 1) * n].copy_from_slice(row);
+        self.add(self.head * n);
+        self.head = (self.head + 1) % self.capacity;
+
+        self.pushes += 1;
+        if self.pushes >= self.capacity {
+            self.resync();
+        }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    pub fn is_full(&self) -> bool { self.len == self.capacity }
+    pub fn capacity(&self) -> usize { self.capacity }
+    pub fn series(&self) -> usize { self.series }
+
+    /// Mean of series `i`.
+    pub fn mean(&self, i: usize) -> f64 { self.mean[i] }
+
+    /// Covariance of series `i` and `j`; 0 while the window is empty.
+    pub fn cov(&self, i: usize, j: usize) -> f64 {
+        if self.len == 0 { 0.0 } else { self.co[i * self.series + j] / self.len as f64 }
+    }
+
+    /// Variance of series `i`.
+    pub fn var(&self, i: usize) -> f64 { self.cov(i, i).max(0.0) }
+
+    /// Newest value of series `i`.
+    pub fn last(&self, i: usize) -> Option<f64> {
+        if self.len == 0 {
+            return None;
+        }
+        let row = (self.head + self.capacity - 1) % self.capacity;
+        Some(self.buf[row * self.series + i])
+    }
+
+    /// Values of series `i`, oldest first.
+    pub fn iter(&self, i: usize) -> impl Iterator<Item = f64> + '_ {
+        let first = (self.head + self.capacity - self.len) % self.capacity;
+        (0..self.len).map(move |k| self.buf[((first + k) % self.capacity) * self.series + i])
+    }
+
+    /// Take in the row starting at `buf[at]`.
+    fn add(&mut self, at: usize) {
+        let n = self.series;
+        self.len += 1;
+        let m = self.len as f64;
+        for i in 0..n {
+            self.dev[i] = self.buf[at + i] - self.mean[i];
+            self.mean[i] += self.dev[i] / m;
+        }
+        // d_i·(x_j − new mean_j) = d_i·d_j·(m−1)/m
+        let f = (m - 1.0) / m;
+        for i in 0..n {
+            for j in 0..n {
+                self.co[i * n + j] += self.dev[i] * self.dev[j] * f;
+            }
+        }
+    }
+
+    /// Drop the row starting at `buf[at]`, the reverse of [`add`](Self::add).
+    fn remove(&mut self, at: usize) {
+        let n = self.series;
+        if self.len <= 1 {
+            self.len = 0;
+            self.mean.fill(0.0);
+            self.co.fill(0.0);
+            return;
+        }
+        let m = self.len as f64;
+        for i in 0..n {
+            self.dev[i] = self.buf[at + i] - self.mean[i];
+        }
+        let f = m / (m - 1.0);
+        for i in 0..n {
+            self.mean[i] -= self.dev[i] / (m - 1.0);
+            for j in 0..n {
+                self.co[i * n + j] -= self.dev[i] * self.dev[j] * f;
+            }
+        }
+        self.len -= 1;
+    }
+
+    /// Recompute mean and co-moments exactly from the buffer.
+    fn resync(&mut self) {
+        let n = self.series;
+        self.pushes = 0;
+        let m = self.len as f64;
+        for i in 0..n {
+            self.mean[i] = self.iter(i).sum::<f64>() / m;
+        }
+        let first = (self.head + self.capacity - self.len) % self.capacity;
+        self.co.fill(0.0);
+        for k in 0..self.len {
+            let row = ((first + k) % self.capacity) * n;
+            for i in 0..n {
+                let di = self.buf[row + i] - self.mean[i];
+                for j in 0..n {
+                    self.co[i * n + j] += di * (self.buf[row + j] - self.mean[j]);
+                }
+            }
+        }
+    }
+}
+
+/// A rolling window over one series.
+#[derive(Debug, Clone)]
+pub struct Rolling(RollingCov);
+
+impl Rolling {
+    /// Window of the last `capacity` values.
+    pub fn new(capacity: usize) -> Self { Self(RollingCov::new(1, capacity)) }
+
+    /// Add a value, dropping the oldest one once the window is full.
+    pub fn push(&mut self, x: f64) { self.0.push(&[x]) }
+
+    pub fn len(&self) -> usize { self.0.len() }
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+    pub fn is_full(&self) -> bool { self.0.is_full() }
+    pub fn capacity(&self) -> usize { self.0.capacity() }
+
+    pub fn mean(&self) -> f64 { self.0.mean(0) }
+    pub fn var(&self) -> f64 { self.0.var(0) }
+    pub fn std(&self) -> f64 { self.var().sqrt() }
+    pub fn last(&self) -> Option<f64> { self.0.last(0) }
+
+    /// Values oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ { self.0.iter(0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic uniform draws in [-0.5, 0.5).
+    fn noise(seed: u64) -> impl FnMut() -> f64 {
+        let mut s = seed;
+        move || {
+            s = s.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (s >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        }
+    }
+
+    /// Mean and population covariance of the last `cap` rows, from scratch.
+    fn naive(rows: &[Vec<f64>], cap: usize, i: usize, j: usize) -> (f64, f64) {
+        let w = &rows[rows.len().saturating_sub(cap)..];
+        let m = w.len() as f64;
+        let (mi, mj) = (w.iter().map(|r| r[i]).sum::<f64>() / m, w.iter().map(|r| r[j]).sum::<f64>() / m);
+        (mi, w.iter().map(|r| (r[i] - mi) * (r[j] - mj)).sum::<f64>() / m)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * (1.0 + a.abs().max(b.abs()))
+    }
+
+    #[test]
+    fn matches_naive_recompute_through_wraparound_and_resync() {
+        let (series, cap) = (3, 37);
+        let mut u = noise(7);
+        let mut w = RollingCov::new(series, cap);
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        let mut level = vec![100.0, -5.0, 1e4];
+        for step in 0..20 * cap + 11 {
+            // correlated random walks far from zero, to stress the updates
+            let common = u();
+            for (k, x) in level.iter_mut().enumerate() {
+                *x += common + 0.3 * u() * (k + 1) as f64;
+            }
+            w.push(&level);
+            rows.push(level.clone());
+
+            assert_eq!(w.len(), rows.len().min(cap));
+            assert_eq!(w.is_full(), rows.len() >= cap);
+            for i in 0..series {
+                for j in 0..series {
+                    let (mean, cov) = naive(&rows, cap, i, j);
+                    assert!(close(w.mean(i), mean), "mean {i} at step {step}: {} vs {mean}", w.mean(i));
+                    assert!(close(w.cov(i, j), cov), "cov {i},{j} at step {step}: {} vs {cov}", w.cov(i, j));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn iter_and_last_follow_the_window() {
+        let mut w = Rolling::new(4);
+        assert!(w.is_empty() && w.last().is_none());
+        for x in 1..=10 {
+            w.push(x as f64);
+        }
+        assert_eq!(w.iter().collect::<Vec<_>>(), vec![7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(w.last(), Some(10.0));
+        assert!(close(w.mean(), 8.5));
+        assert!(close(w.var(), 1.25));
+    }
+}
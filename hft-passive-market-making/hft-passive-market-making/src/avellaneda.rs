@@ -0,0 +1,88 @@
+//! Avellaneda–Stoikov (2008) quoting: skew a reservation price away from
+//! inventory and quote an optimal spread around it.
+//!
+//! ```text
+//! r = s − q·γ·σ²·τ
+//! δ = γ·σ²·τ + (2/γ)·ln(1 + γ/κ)        (bid, ask) = r ∓ δ/2
+//! ```
+//!
+//! `s` is the mid, `q` inventory, `σ²` the mid's variance per second (from
+//! [`Volatility`](crate::estimators::Volatility)) and `τ` the time left to
+//! the horizon.
+
+use crate::config::AsCfg;
+use std::time::Instant;
+
+pub struct AvellanedaStoikov {
+    cfg:   AsCfg,
+    start: Option<Instant>,
+}
+
+impl AvellanedaStoikov {
+    pub fn new(cfg: AsCfg) -> Self {
+        Self { cfg, start: None }
+    }
+
+    /// Seconds left to the end of the current horizon, counted from the
+    /// first quote.
+    pub fn tau(&mut self, now: Instant) -> f64 {
+        let horizon = self.cfg.horizon_secs;
+        let start = *self.start.get_or_insert(now);
+        horizon - now.saturating_duration_since(start).as_secs_f64() % horizon
+    }
+
+    /// (bid, ask) for `inv` contracts around `mid`, given the mid's
+    /// variance per second `sigma2`.
+    pub fn quotes(&mut self, mid: f64, inv: f64, sigma2: f64, now: Instant) -> (f64, f64) {
+        let AsCfg { gamma, kappa, .. } = self.cfg;
+        let risk = gamma * sigma2 * self.tau(now);
+        let reservation = mid - inv * risk;
+        let half = (risk + 2.0 / gamma * (1.0 + gamma / kappa).ln()) / 2.0;
+        (reservation - half, reservation + half)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn model() -> AvellanedaStoikov {
+        AvellanedaStoikov::new(AsCfg { gamma: 0.1, kappa: 2.0, horizon_secs: 30.0 })
+    }
+
+    #[test]
+    fn quotes_match_the_closed_form() {
+        // τ = 30 at the first quote: γσ²τ = 0.1·0.01·30 = 0.03,
+        // (2/γ)·ln(1 + γ/κ) = 20·ln 1.05 = 0.975803…, half spread 0.502902…
+        let (mut m, t0) = (model(), Instant::now());
+        let (bid, ask) = m.quotes(100.0, 2.0, 0.01, t0);
+        let r = 99.94; // 100 − q·γσ²τ = 100 − 2·0.03
+        assert!((bid - 99.437_098_35).abs() < 1e-8, "{bid}");
+        assert!((ask - 100.442_901_65).abs() < 1e-8, "{ask}");
+        assert!(((bid + ask) / 2.0 - r).abs() < 1e-12);
+    }
+
+    #[test]
+    fn inventory_skews_the_reservation_away_from_the_position() {
+        let (mut m, t0) = (model(), Instant::now());
+        let mid_of = |(b, a): (f64, f64)| (b + a) / 2.0;
+        let flat  = mid_of(m.quotes(100.0, 0.0, 0.01, t0));
+        let long  = mid_of(m.quotes(100.0, 2.0, 0.01, t0));
+        let short = mid_of(m.quotes(100.0, -2.0, 0.01, t0));
+        assert!((flat - 100.0).abs() < 1e-12);
+        assert!(long < flat && (long - 99.94).abs() < 1e-12, "long should quote lower: {long}");
+        assert!(short > flat && (short - 100.06).abs() < 1e-12, "short should quote higher: {short}");
+    }
+
+    #[test]
+    fn tau_counts_down_and_rolls_over() {
+        let (mut m, t0) = (model(), Instant::now());
+        assert_eq!(m.tau(t0), 30.0);
+        assert!((m.tau(t0 + Duration::from_secs(10)) - 20.0).abs() < 1e-9);
+        assert!((m.tau(t0 + Duration::from_secs(40)) - 20.0).abs() < 1e-9);
+        // the risk term shrinks with τ: 0.1·0.01·20 = 0.02 → r = 100 − 2·0.02
+        let (bid, ask) = m.quotes(100.0, 2.0, 0.01, t0 + Duration::from_secs(10));
+        assert!(((bid + ask) / 2.0 - 99.96).abs() < 1e-12);
+    }
+}
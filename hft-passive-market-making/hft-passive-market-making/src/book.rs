@@ -0,0 +1,315 @@
+//! Price-time priority limit-order-book used by the exchange simulator.
+//!
+//! Prices are stored as integer ticks so levels compare exactly; every level
+//! is a FIFO queue, so an order only trades once everything ahead of it at
+//! the same price has been consumed.
+
+use hft_core::{Side, DEPTH};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+pub type BookId = u64;
+
+/// Who owns a resting order — our strategy or the simulated crowd.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner { Ours, Sim }
+
+#[derive(Debug, Clone)]
+struct Resting {
+    id:    BookId,
+    owner: Owner,
+    qty:   f64,
+}
+
+/// One execution between an aggressor and a resting (maker) order.
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub maker_id:    BookId,
+    pub maker_owner: Owner,
+    /// side of the resting order
+    pub maker_side:  Side,
+    pub px:          f64,
+    pub qty:         f64,
+}
+
+pub struct Book {
+    tick_sz: f64,
+    bids:    BTreeMap<i64, VecDeque<Resting>>,   // tick → FIFO queue
+    asks:    BTreeMap<i64, VecDeque<Resting>>,
+    index:   HashMap<BookId, (Side, i64)>,        // id → (side, level)
+    next_id: BookId,
+}
+
+const QTY_EPS: f64 = 1e-9;
+
+impl Book {
+    pub fn new(tick_sz: f64) -> Self {
+        Self {
+            tick_sz,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            index: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /* ────────── price helpers ────────── */
+
+    /// Snap a price onto the grid; bids round down and asks round up so a
+    /// passive order never becomes more aggressive than requested.
+    pub fn to_tick(&self, side: Side, px: f64) -> i64 {
+        let t = px / self.tick_sz;
+        match side {
+            Side::Buy  => (t + 1e-9).floor() as i64,
+            Side::Sell => (t - 1e-9).ceil() as i64,
+        }
+    }
+
+    pub fn to_px(&self, tick: i64) -> f64 { tick as f64 * self.tick_sz }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.keys().next_back().map(|&t| self.to_px(t))
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().map(|&t| self.to_px(t))
+    }
+
+    /// Total size at the given price on one side.
+    pub fn level_qty(&self, side: Side, px: f64) -> f64 {
+        let tick = self.to_tick(side, px);
+        self.side(side).get(&tick).map_or(0.0, |q| q.iter().map(|r| r.qty).sum())
+    }
+
+    /// Resting size on the first [`DEPTH`] levels of one side, best first.
+    pub fn depth(&self, side: Side) -> [f64; DEPTH] {
+        let level = |q: &VecDeque<Resting>| q.iter().map(|r| r.qty).sum::<f64>();
+        let mut out = [0.0; DEPTH];
+        match side {
+            Side::Buy  => out.iter_mut().zip(self.bids.values().rev()).for_each(|(o, q)| *o = level(q)),
+            Side::Sell => out.iter_mut().zip(self.asks.values()).for_each(|(o, q)| *o = level(q)),
+        }
+        out
+    }
+
+    /* ────────── order entry ────────── */
+
+    /// Submit a limit order: it first trades against the opposite side as far
+    /// as its price allows, then any remainder rests at the back of its level.
+    /// Returns the id of the resting remainder (if any) and the executions.
+    pub fn limit(&mut self, owner: Owner, side: Side, px: f64, qty: f64) -> (Option<BookId>, Vec<Match>) {
+        let tick = self.to_tick(side, px);
+        let (left, matches) = self.sweep(side, qty, Some(tick));
+        if left <= QTY_EPS {
+            return (None, matches);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.side_mut(side)
+            .entry(tick)
+            .or_default()
+            .push_back(Resting { id, owner, qty: left });
+        self.index.insert(id, (side, tick));
+        (Some(id), matches)
+    }
+
+    /// Marketable order with no price limit; unfilled size is discarded.
+    pub fn market(&mut self, side: Side, qty: f64) -> Vec<Match> {
+        self.sweep(side, qty, None).1
+    }
+
+    /// Remove a resting order, returning its open quantity.
+    pub fn cancel(&mut self, id: BookId) -> Option<f64> {
+        let (side, tick) = self.index.remove(&id)?;
+        let book = self.side_mut(side);
+        let queue = book.get_mut(&tick)?;
+        let pos = queue.iter().position(|r| r.id == id)?;
+        let gone = queue.remove(pos).map(|r| r.qty);
+        if queue.is_empty() {
+            book.remove(&tick);
+        }
+        gone
+    }
+
+    /// Shrink a resting order in place; it keeps its queue position.
+    pub fn reduce(&mut self, id: BookId, qty: f64) -> bool {
+        let Some(&(side, tick)) = self.index.get(&id) else { return false };
+        let Some(r) = self.side_mut(side).get_mut(&tick)
+            .and_then(|q| q.iter_mut().find(|r| r.id == id)) else { return false };
+        if qty > r.qty || qty <= QTY_EPS {
+            return false;
+        }
+        r.qty = qty;
+        true
+    }
+
+    /// Cancel every resting order that `keep` rejects (used to age out
+    /// simulated liquidity that has drifted away from fair value).
+    pub fn retain(&mut self, mut keep: impl FnMut(Owner, Side, f64) -> bool) {
+        let tick_sz = self.tick_sz;
+        let mut dead = Vec::new();
+        for (side, book) in [(Side::Buy, &self.bids), (Side::Sell, &self.asks)] {
+            for (&t, q) in book {
+                for r in q {
+                    if !keep(r.owner, side, t as f64 * tick_sz) {
+                        dead.push(r.id);
+                    }
+                }
+            }
+        }
+        for id in dead {
+            self.cancel(id);
+        }
+    }
+
+    /* ────────── queue introspection ────────── */
+
+    /// Open quantity of a resting order.
+    pub fn open_qty(&self, id: BookId) -> Option<f64> {
+        let (side, tick) = self.index.get(&id)?;
+        self.side(*side).get(tick)?.iter().find(|r| r.id == id).map(|r| r.qty)
+    }
+
+    /// Price level a resting order sits at.
+    pub fn price_of(&self, id: BookId) -> Option<f64> {
+        self.index.get(&id).map(|&(_, tick)| self.to_px(tick))
+    }
+
+    /// Quantity queued ahead of `id` at its price level.
+    pub fn queue_ahead(&self, id: BookId) -> Option<f64> {
+        let (side, tick) = self.index.get(&id)?;
+        let mut ahead = 0.0;
+        for r in self.side(*side).get(tick)? {
+            if r.id == id {
+                return Some(ahead);
+            }
+            ahead += r.qty;
+        }
+        None
+    }
+
+    /* ────────── matching ────────── */
+
+    fn sweep(&mut self, side: Side, mut qty: f64, limit: Option<i64>) -> (f64, Vec<Match>) {
+        let mut matches = Vec::new();
+        let maker_side = match side { Side::Buy => Side::Sell, Side::Sell => Side::Buy };
+
+        while qty > QTY_EPS {
+            let best = match side {
+                Side::Buy  => self.asks.keys().next().copied(),
+                Side::Sell => self.bids.keys().next_back().copied(),
+            };
+            let Some(tick) = best else { break };
+            let crosses = match (side, limit) {
+                (_, None)              => true,
+                (Side::Buy,  Some(l))  => tick <= l,
+                (Side::Sell, Some(l))  => tick >= l,
+            };
+            if !crosses { break; }
+
+            let px = self.to_px(tick);
+            let mut done = Vec::new();
+            let book = match maker_side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks };
+            let queue = book.get_mut(&tick).expect("best level exists");
+            while qty > QTY_EPS {
+                let Some(head) = queue.front_mut() else { break };
+                let traded = head.qty.min(qty);
+                head.qty -= traded;
+                qty -= traded;
+                matches.push(Match {
+                    maker_id: head.id,
+                    maker_owner: head.owner,
+                    maker_side,
+                    px,
+                    qty: traded,
+                });
+                if head.qty <= QTY_EPS {
+                    done.push(head.id);
+                    queue.pop_front();
+                }
+            }
+            if queue.is_empty() {
+                book.remove(&tick);
+            }
+            for id in done {
+                self.index.remove(&id);
+            }
+        }
+        (qty, matches)
+    }
+
+    fn side(&self, side: Side) -> &BTreeMap<i64, VecDeque<Resting>> {
+        match side { Side::Buy => &self.bids, Side::Sell => &self.asks }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<i64, VecDeque<Resting>> {
+        match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qtys(matches: &[Match]) -> Vec<(BookId, f64, f64)> {
+        matches.iter().map(|m| (m.maker_id, m.px, m.qty)).collect()
+    }
+
+    #[test]
+    fn same_price_fills_first_come_first_served() {
+        let mut book = Book::new(0.5);
+        let a = book.limit(Owner::Sim, Side::Sell, 100.0, 2.0).0.unwrap();
+        let b = book.limit(Owner::Ours, Side::Sell, 100.0, 3.0).0.unwrap();
+        let c = book.limit(Owner::Sim, Side::Sell, 100.0, 1.0).0.unwrap();
+        assert_eq!(book.queue_ahead(c), Some(5.0));
+        assert_eq!(book.level_qty(Side::Sell, 100.0), 6.0);
+
+        assert_eq!(qtys(&book.market(Side::Buy, 4.0)), vec![(a, 100.0, 2.0), (b, 100.0, 2.0)]);
+        assert_eq!(book.open_qty(a), None);
+        assert_eq!(book.open_qty(b), Some(1.0));
+        assert_eq!(book.queue_ahead(b), Some(0.0));
+        assert_eq!(book.queue_ahead(c), Some(1.0));
+    }
+
+    #[test]
+    fn better_prices_trade_first_and_the_remainder_rests() {
+        let mut book = Book::new(0.5);
+        let far = book.limit(Owner::Sim, Side::Sell, 101.0, 1.0).0.unwrap();
+        let near = book.limit(Owner::Sim, Side::Sell, 100.5, 1.0).0.unwrap();
+        book.limit(Owner::Sim, Side::Sell, 102.0, 1.0);
+
+        // a buy limit at 101 takes both levels up to its price, rests the rest
+        let (rest, matches) = book.limit(Owner::Ours, Side::Buy, 101.0, 3.0);
+        assert_eq!(qtys(&matches), vec![(near, 100.5, 1.0), (far, 101.0, 1.0)]);
+        let rest = rest.unwrap();
+        assert_eq!(book.open_qty(rest), Some(1.0));
+        assert_eq!(book.best_bid(), Some(101.0));
+        assert_eq!(book.best_ask(), Some(102.0));
+    }
+
+    #[test]
+    fn passive_prices_round_away_from_the_touch() {
+        let mut book = Book::new(0.5);
+        let bid = book.limit(Owner::Ours, Side::Buy, 99.7, 1.0).0.unwrap();
+        let ask = book.limit(Owner::Ours, Side::Sell, 100.2, 1.0).0.unwrap();
+        assert_eq!(book.price_of(bid), Some(99.5));
+        assert_eq!(book.price_of(ask), Some(100.5));
+    }
+
+    #[test]
+    fn reduce_keeps_the_queue_position_and_cancel_clears_the_level() {
+        let mut book = Book::new(0.5);
+        let a = book.limit(Owner::Ours, Side::Buy, 99.0, 3.0).0.unwrap();
+        let b = book.limit(Owner::Sim, Side::Buy, 99.0, 1.0).0.unwrap();
+        assert!(book.reduce(a, 2.0));
+        assert!(!book.reduce(a, 5.0), "reduce cannot grow an order");
+        assert_eq!(book.queue_ahead(b), Some(2.0));
+
+        assert_eq!(book.cancel(a), Some(2.0));
+        assert_eq!(book.cancel(a), None);
+        assert_eq!(book.queue_ahead(b), Some(0.0));
+        assert_eq!(book.cancel(b), Some(1.0));
+        assert_eq!(book.best_bid(), None);
+        assert!(book.market(Side::Sell, 1.0).is_empty());
+    }
+}
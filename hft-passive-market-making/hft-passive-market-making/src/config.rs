@@ -1,3 +1,4 @@
+use hft_core::FeeSchedule;
 use serde::Deserialize;
 use figment::providers::{Env, Toml, Format};  // ← `Format` gives Toml::file()
 
@@ -7,20 +8,376 @@ pub struct MmCfg {
     pub symbol: String,
     /// ticks half-spread when flat
     pub half_spread: f64,
-    /// contracts per quote side
+    /// minimum price increment of the simulated book
+    pub tick_sz: f64,
+    /// contracts per quote side (per level without a `[ladder]`)
     pub size: f64,
     /// inventory hard-limit
     pub inv_limit: f64,
-    /// extra spread at max inventory (multiplier)
+    /// extra spread at max inventory (multiplier), linear model only
     pub inv_spread_mult: f64,
+    /// simulator start price
+    #[serde(default = "default_mid_px")]
+    pub mid_px: f64,
+    /// more symbols to quote alongside `symbol`, each with its own book
+    #[serde(default)]
+    pub symbols: Vec<SymbolCfg>,
+    /// cap on Σ |inventory| over all symbols, in contracts
+    pub gross_inv_limit: Option<f64>,
+    /// order messages per second, over all symbols
+    #[serde(default = "default_max_orders_sec")]
+    pub max_orders_sec: usize,
+    /// how quotes are placed around the mid
+    #[serde(default)]
+    pub model: QuoteModel,
+    /// parameters of `model = "avellaneda_stoikov"`
+    pub avellaneda_stoikov: Option<AsCfg>,
+    /// volatility and trade-arrival estimators
+    pub estimators: EstCfg,
+    /// scale `half_spread` with the measured market regime (linear model)
+    pub adaptive_spread: Option<AdaptiveCfg>,
+    /// several quote levels per side instead of one
+    pub ladder: Option<LadderCfg>,
+    /// leave working quotes alone until they are far enough off or too old
+    pub refresh: Option<RefreshCfg>,
+    /// post-fill markouts of our fills
+    pub markouts: Option<MarkoutCfg>,
+    /// offset inventory on a correlated instrument
+    pub hedge: Option<HedgeCfg>,
+    /// stop quoting and work inventory down before the session ends
+    pub liquidation: Option<LiquidationCfg>,
+    /// simulated traders sending market orders to the venue
+    #[serde(default)]
+    pub flow: FlowCfg,
     /// simulator tick interval (ms)
     pub tick_ms: u64,
+    /// venue fee schedule: maker / taker bps and per-contract fees
+    /// (negative = rebate), optionally tiered by traded notional
+    #[serde(default)]
+    pub fees: FeeSchedule,
+    /// seconds of session time between PnL summaries
+    pub summary_secs: u64,
+    /// simulator RNG seed — same seed, same session
+    pub seed: u64,
+    /// run on virtual time as fast as the CPU allows instead of real time
+    #[serde(default)]
+    pub backtest: bool,
+    /// end the session after this many ticks (runs forever if unset)
+    pub max_ticks: Option<u64>,
+    /// write every market-data event and execution to this JSONL file
+    pub record: Option<String>,
+    /// replay this recording instead of running the simulator
+    pub replay: Option<String>,
+    /// run on historical CSV/Parquet quotes instead of the simulator
+    pub data: Option<String>,
+    /// Sharpe/Sortino bucket of the end-of-run report, in ms of session time
+    pub report_bucket_ms: u64,
+    /// also write the end-of-run report to this JSON file
+    pub report_json: Option<String>,
+}
+
+fn default_mid_px() -> f64 { 100.0 }
+fn default_max_orders_sec() -> usize { 20 }
+
+/// A further symbol to quote; knobs left out take the top-level value.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SymbolCfg {
+    pub symbol: String,
+    pub mid_px: Option<f64>,
+    pub half_spread: Option<f64>,
+    pub tick_sz: Option<f64>,
+    pub size: Option<f64>,
+    pub inv_limit: Option<f64>,
+    pub inv_spread_mult: Option<f64>,
+}
+
+/// Quoting model of [`InventoryMm`](crate::strategy::InventoryMm).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteModel {
+    /// `half_spread` around the mid, widened on both sides with inventory
+    #[default]
+    Linear,
+    /// reservation price and optimal spread of Avellaneda & Stoikov (2008)
+    AvellanedaStoikov,
+}
+
+/// Avellaneda–Stoikov knobs.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AsCfg {
+    /// risk aversion γ: how hard inventory pushes the reservation price
+    pub gamma: f64,
+    /// order-arrival decay κ: fill intensity falls as e^(−κ·distance)
+    pub kappa: f64,
+    /// trading horizon in seconds of session time; rolls over when reached
+    pub horizon_secs: f64,
+}
+
+/// How realized volatility is averaged.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VolKind {
+    /// exponentially weighted, span `vol_window` ticks
+    #[default]
+    Ewma,
+    /// equally weighted over the last `vol_window` ticks
+    Rolling,
+}
+
+/// Market estimators, see [`estimators`](crate::estimators).
+#[derive(Debug, Deserialize, Clone)]
+pub struct EstCfg {
+    #[serde(default)]
+    pub vol: VolKind,
+    /// ticks of volatility memory
+    pub vol_window: usize,
+    /// seconds of session time the trade-arrival rate is counted over
+    pub arrival_window_secs: f64,
+}
+
+/// Regime scaling of the linear spread: `half_spread` applies as-is when
+/// volatility and trade arrivals sit at their reference levels, and is
+/// multiplied by √(σ/vol_ref · λ/arrival_ref) otherwise, within bounds.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdaptiveCfg {
+    /// σ of the mid, price per √second
+    pub vol_ref: f64,
+    /// trades per second
+    pub arrival_ref: f64,
+    pub min_mult: f64,
+    pub max_mult: f64,
+}
+
+/// How far apart ladder levels sit.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Spacing {
+    /// every gap is `step`
+    #[default]
+    Linear,
+    /// gaps grow by `ratio`: step, step·ratio, step·ratio², …
+    Geometric,
+}
+
+/// Quote ladder behind the model's bid and ask, see [`ladder`](crate::ladder).
+#[derive(Debug, Deserialize, Clone)]
+pub struct LadderCfg {
+    /// levels per side, including the top one
+    pub levels: usize,
+    #[serde(default)]
+    pub spacing: Spacing,
+    /// price gap between the first two levels
+    pub step: f64,
+    /// gap growth per level (geometric spacing)
+    #[serde(default)]
+    pub ratio: f64,
+    /// contracts per level, top first; missing levels repeat the last entry
+    /// (or `size` if empty)
+    #[serde(default)]
+    pub sizes: Vec<f64>,
+}
+
+/// Quote refresh throttling: a working quote is amended only when its
+/// target moved by at least `min_move_ticks` or it is `max_age_ms` old.
+/// Size changes (a partial fill to top up) always go out.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RefreshCfg {
+    pub min_move_ticks: f64,
+    /// amend a quote this old on any price change (never if unset)
+    pub max_age_ms: Option<u64>,
+}
+
+/// Markout analysis, see [`markout`](crate::markout).
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarkoutCfg {
+    /// horizons after each fill, ms of session time, ascending
+    pub horizons_ms: Vec<u64>,
+    /// ascending edges of the inventory-before-fill buckets, in contracts
+    #[serde(default)]
+    pub inv_buckets: Vec<f64>,
+    /// write one row per fill to this CSV file
+    pub csv: Option<String>,
+    /// write the markout curves to this JSON file
+    pub json: Option<String>,
+}
+
+/// One population of simulated traders, see [`flow`](crate::flow).
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct AgentCfg {
+    /// market orders per second of session time (Poisson arrivals)
+    pub rate: f64,
+    pub min_qty: f64,
+    pub max_qty: f64,
+}
+
+/// Order-flow mix of the simulated venue.
+#[derive(Debug, Deserialize, Clone)]
+pub struct FlowCfg {
+    /// random side
+    pub noise: AgentCfg,
+    /// follow the trend of the mid over `momentum_lookback` ticks
+    pub momentum: AgentCfg,
+    pub momentum_lookback: usize,
+    /// trade ahead of the next `informed_horizon` fair-value moves
+    pub informed: AgentCfg,
+    pub informed_horizon: usize,
+}
+
+impl Default for FlowCfg {
+    /// Noise traders only.
+    fn default() -> Self {
+        let off = AgentCfg { rate: 0.0, min_qty: 1.0, max_qty: 1.0 };
+        Self {
+            noise: AgentCfg { rate: 6.0, min_qty: 0.5, max_qty: 3.0 },
+            momentum: off,
+            momentum_lookback: 20,
+            informed: off,
+            informed_horizon: 1,
+        }
+    }
+}
+
+/// Inventory hedging, see [`hedge`](crate::hedge).
+#[derive(Debug, Deserialize, Clone)]
+pub struct HedgeCfg {
+    /// instrument the hedge trades
+    pub symbol: String,
+    /// hedge contracts per contract of our inventory
+    pub ratio: f64,
+    /// unhedged inventory that triggers a hedge, in our contracts
+    pub threshold: f64,
+    /// simulator: correlation of the hedge's price moves with ours
+    pub correlation: f64,
+    /// simulator: bid–ask spread of the hedge instrument
+    pub spread: f64,
+}
+
+/// How [`Liquidator`](crate::liquidation::Liquidator) works inventory down.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LiqMethod {
+    /// equal market-order slices until the deadline
+    #[default]
+    Twap,
+    /// the whole remainder as an immediate-or-cancel limit each slice
+    Ioc,
+}
+
+/// End-of-session liquidation, see [`liquidation`](crate::liquidation).
+#[derive(Debug, Deserialize, Clone)]
+pub struct LiquidationCfg {
+    /// seconds of session time after which quoting stops
+    pub stop_secs: f64,
+    /// seconds after the stop to work inventory before a hard flatten
+    pub flatten_secs: f64,
+    #[serde(default)]
+    pub method: LiqMethod,
+    /// ms between child orders
+    pub slice_ms: u64,
+    /// ioc: ticks through the touch a child order may trade
+    #[serde(default)]
+    pub max_slip_ticks: f64,
 }
 
 impl MmCfg {
     pub fn load() -> anyhow::Result<Self> {
-        Ok(figment::Figment::from(Toml::file("Config.toml"))
-            .merge(Env::prefixed("MM_"))
-            .extract()?)
+        Self::extract(figment::Figment::from(Toml::file("Config.toml")).merge(Env::prefixed("MM_")))
     }
+
+    /// Deserialize and validate the config `figment` provides.
+    pub fn extract(figment: figment::Figment) -> anyhow::Result<Self> {
+        let cfg: Self = figment.extract()?;
+        anyhow::ensure!(
+            cfg.model != QuoteModel::AvellanedaStoikov || cfg.avellaneda_stoikov.is_some(),
+            "model = \"avellaneda_stoikov\" needs an [avellaneda_stoikov] table"
+        );
+        if let Some(a) = &cfg.avellaneda_stoikov {
+            anyhow::ensure!(a.gamma > 0.0 && a.kappa > 0.0 && a.horizon_secs > 0.0,
+                            "avellaneda_stoikov needs gamma, kappa and horizon_secs > 0");
+        }
+        anyhow::ensure!(cfg.estimators.vol_window >= 1, "estimators.vol_window must be at least 1");
+        anyhow::ensure!(cfg.estimators.arrival_window_secs > 0.0,
+                        "estimators.arrival_window_secs must be positive");
+        if let Some(l) = &cfg.ladder {
+            anyhow::ensure!(l.levels >= 1, "ladder.levels must be at least 1");
+            anyhow::ensure!(l.spacing != Spacing::Geometric || l.ratio > 0.0,
+                            "geometric ladder needs ladder.ratio > 0");
+        }
+        for (name, a) in [("noise", cfg.flow.noise), ("momentum", cfg.flow.momentum), ("informed", cfg.flow.informed)] {
+            anyhow::ensure!(a.rate >= 0.0 && 0.0 < a.min_qty && a.min_qty <= a.max_qty,
+                            "flow.{name} needs rate >= 0 and 0 < min_qty <= max_qty");
+        }
+        anyhow::ensure!(cfg.flow.momentum_lookback >= 1 && cfg.flow.informed_horizon >= 1,
+                        "flow.momentum_lookback and flow.informed_horizon must be at least 1");
+        if let Some(h) = &cfg.hedge {
+            anyhow::ensure!(h.symbol != cfg.symbol, "hedge.symbol must differ from symbol");
+            anyhow::ensure!(h.ratio > 0.0 && h.threshold >= 0.0,
+                            "hedge needs ratio > 0 and threshold >= 0");
+            anyhow::ensure!((-1.0..=1.0).contains(&h.correlation),
+                            "hedge.correlation must be within [-1, 1]");
+        }
+        if let Some(l) = &cfg.liquidation {
+            anyhow::ensure!(l.stop_secs >= 0.0 && l.flatten_secs >= 0.0 && l.max_slip_ticks >= 0.0,
+                            "liquidation needs stop_secs, flatten_secs and max_slip_ticks >= 0");
+            anyhow::ensure!(l.slice_ms > 0, "liquidation.slice_ms must be positive");
+        }
+        anyhow::ensure!(cfg.tick_sz > 0.0, "tick_sz must be positive");
+        let mut symbols = vec![cfg.symbol.as_str()];
+        symbols.extend(cfg.hedge.as_ref().map(|h| h.symbol.as_str()));
+        for s in &cfg.symbols {
+            anyhow::ensure!(!symbols.contains(&s.symbol.as_str()), "symbol {} is listed twice", s.symbol);
+            anyhow::ensure!(s.tick_sz.is_none_or(|t| t > 0.0), "symbols.{}: tick_sz must be positive", s.symbol);
+            symbols.push(&s.symbol);
+        }
+        anyhow::ensure!(cfg.gross_inv_limit.is_none_or(|l| l > 0.0), "gross_inv_limit must be positive");
+        anyhow::ensure!(cfg.report_bucket_ms > 0, "report_bucket_ms must be positive");
+        if let Some(m) = &cfg.markouts {
+            anyhow::ensure!(m.horizons_ms.windows(2).all(|w| w[0] < w[1]),
+                            "markouts.horizons_ms must be ascending");
+            anyhow::ensure!(m.inv_buckets.windows(2).all(|w| w[0] < w[1]),
+                            "markouts.inv_buckets must be ascending");
+        }
+        Ok(cfg)
+    }
+
+    /// One config per quoted symbol, `symbol` first, with the per-symbol
+    /// overrides applied. Only the first symbol is hedged, and with several
+    /// symbols the markout files get the symbol appended to their name.
+    pub fn books(&self) -> Vec<MmCfg> {
+        let first = MmCfg { symbols: Vec::new(), ..self.clone() };
+        let mut books = vec![first.clone()];
+        for s in &self.symbols {
+            books.push(MmCfg {
+                symbol:          s.symbol.clone(),
+                mid_px:          s.mid_px.unwrap_or(self.mid_px),
+                half_spread:     s.half_spread.unwrap_or(self.half_spread),
+                tick_sz:         s.tick_sz.unwrap_or(self.tick_sz),
+                size:            s.size.unwrap_or(self.size),
+                inv_limit:       s.inv_limit.unwrap_or(self.inv_limit),
+                inv_spread_mult: s.inv_spread_mult.unwrap_or(self.inv_spread_mult),
+                hedge:           None,
+                ..first.clone()
+            });
+        }
+        if books.len() > 1 {
+            for b in &mut books {
+                if let Some(m) = &mut b.markouts {
+                    m.csv = m.csv.as_deref().map(|p| suffixed(p, &b.symbol));
+                    m.json = m.json.as_deref().map(|p| suffixed(p, &b.symbol));
+                }
+            }
+        }
+        books
+    }
+}
+
+/// `markouts.csv` → `markouts_SYM.csv`
+fn suffixed(path: &str, symbol: &str) -> String {
+    let p = std::path::Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or(path);
+    let name = match p.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{stem}_{symbol}.{ext}"),
+        None      => format!("{stem}_{symbol}"),
+    };
+    p.with_file_name(name).to_string_lossy().into_owned()
 }
@@ -0,0 +1,198 @@
+//! Online market estimators fed from the market-data stream: realized
+//! volatility of the mid (from ticks) and the arrival rate of trades (from
+//! tape prints). Both are in session time, so backtests see the same values
+//! as paced runs.
+
+use crate::config::{EstCfg, VolKind};
+use hft_core::MarketData;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Realized variance of the mid, in price² per second.
+pub struct Volatility {
+    kind:   VolKind,
+    /// EWMA span or rolling length, in ticks
+    window: usize,
+    ewma:   Option<f64>,
+    /// rolling samples (dt seconds, squared mid change) and their sums
+    samples: VecDeque<(f64, f64)>,
+    sum_dt:  f64,
+    sum_sq:  f64,
+    last:   Option<(Instant, f64)>,
+}
+
+impl Volatility {
+    pub fn new(kind: VolKind, window: usize) -> Self {
+        Self {
+            kind,
+            window: window.max(1),
+            ewma: None,
+            samples: VecDeque::new(),
+            sum_dt: 0.0,
+            sum_sq: 0.0,
+            last: None,
+        }
+    }
+
+    pub fn on_mid(&mut self, ts: Instant, mid: f64) {
+        if let Some((t, prev)) = self.last {
+            let dt = ts.saturating_duration_since(t).as_secs_f64();
+            if dt > 0.0 {
+                self.sample(dt, (mid - prev).powi(2));
+            }
+        }
+        self.last = Some((ts, mid));
+    }
+
+    fn sample(&mut self, dt: f64, sq: f64) {
+        match self.kind {
+            VolKind::Ewma => {
+                let alpha = 2.0 / (self.window as f64 + 1.0);
+                let x = sq / dt;
+                self.ewma = Some(self.ewma.map_or(x, |v| v + alpha * (x - v)));
+            }
+            VolKind::Rolling => {
+                self.samples.push_back((dt, sq));
+                self.sum_dt += dt;
+                self.sum_sq += sq;
+                if self.samples.len() > self.window {
+                    let (dt, sq) = self.samples.pop_front().unwrap_or_default();
+                    self.sum_dt -= dt;
+                    self.sum_sq -= sq;
+                }
+            }
+        }
+    }
+
+    /// `None` until the first mid change has been seen.
+    pub fn var(&self) -> Option<f64> {
+        match self.kind {
+            VolKind::Ewma    => self.ewma,
+            VolKind::Rolling => (self.sum_dt > 0.0).then(|| self.sum_sq.max(0.0) / self.sum_dt),
+        }
+    }
+
+    /// Standard deviation of the mid per √second.
+    pub fn sigma(&self) -> Option<f64> { self.var().map(f64::sqrt) }
+}
+
+/// Trades per second over a sliding window of session time.
+pub struct ArrivalRate {
+    window: Duration,
+    prints: VecDeque<Instant>,
+    start:  Option<Instant>,
+    now:    Option<Instant>,
+}
+
+impl ArrivalRate {
+    pub fn new(window: Duration) -> Self {
+        Self { window, prints: VecDeque::new(), start: None, now: None }
+    }
+
+    pub fn on_trade(&mut self, ts: Instant) {
+        self.prints.push_back(ts);
+        self.advance(ts);
+    }
+
+    /// Move the window forward to `now`.
+    pub fn advance(&mut self, now: Instant) {
+        self.start.get_or_insert(now);
+        self.now = Some(now);
+        while self.prints.front().is_some_and(|&t| now.saturating_duration_since(t) > self.window) {
+            self.prints.pop_front();
+        }
+    }
+
+    /// `None` until a full window of session time has been observed.
+    pub fn rate(&self) -> Option<f64> {
+        let (start, now) = (self.start?, self.now?);
+        (now.saturating_duration_since(start) >= self.window)
+            .then(|| self.prints.len() as f64 / self.window.as_secs_f64())
+    }
+}
+
+/// The estimators [`InventoryMm`](crate::strategy::InventoryMm) keeps for
+/// its symbol.
+pub struct Estimators {
+    pub vol:      Volatility,
+    pub arrivals: ArrivalRate,
+}
+
+impl Estimators {
+    pub fn new(cfg: &EstCfg) -> Self {
+        Self {
+            vol:      Volatility::new(cfg.vol, cfg.vol_window),
+            arrivals: ArrivalRate::new(Duration::from_secs_f64(cfg.arrival_window_secs)),
+        }
+    }
+
+    pub fn on_market_data(&mut self, md: &MarketData) {
+        match md {
+            MarketData::Tick(t)  => {
+                self.vol.on_mid(t.ts, t.mid());
+                self.arrivals.advance(t.ts);
+            }
+            MarketData::Trade(t) => self.arrivals.on_trade(t.ts),
+            MarketData::Book(_)  => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(vol: &mut Volatility, t0: Instant, mids: &[(u64, f64)]) {
+        for &(ms, mid) in mids {
+            vol.on_mid(t0 + Duration::from_millis(ms), mid);
+        }
+    }
+
+    // squared changes per second: 1/1, 4/1, 1/2
+    const MIDS: [(u64, f64); 4] = [(0, 100.0), (1000, 101.0), (2000, 99.0), (4000, 100.0)];
+
+    #[test]
+    fn ewma_variance_on_a_fixed_path() {
+        // span 3 → α = 0.5: 1, then 1 + ½(4 − 1) = 2.5, then 2.5 + ½(0.5 − 2.5) = 1.5
+        let (mut v, t0) = (Volatility::new(VolKind::Ewma, 3), Instant::now());
+        feed(&mut v, t0, &MIDS[..1]);
+        assert_eq!(v.var(), None);
+        feed(&mut v, t0, &MIDS[1..2]);
+        assert_eq!(v.var(), Some(1.0));
+        feed(&mut v, t0, &MIDS[2..]);
+        assert!((v.var().unwrap() - 1.5).abs() < 1e-12);
+        assert!((v.sigma().unwrap() - 1.5f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rolling_variance_keeps_the_last_window() {
+        let (mut v, t0) = (Volatility::new(VolKind::Rolling, 2), Instant::now());
+        feed(&mut v, t0, &MIDS[..3]);
+        assert!((v.var().unwrap() - 5.0 / 2.0).abs() < 1e-12);
+        // the first sample drops out: (4 + 1) / (1 + 2)
+        feed(&mut v, t0, &MIDS[3..]);
+        assert!((v.var().unwrap() - 5.0 / 3.0).abs() < 1e-12);
+        // a repeated timestamp is not a sample
+        v.on_mid(t0 + Duration::from_millis(4000), 150.0);
+        assert!((v.var().unwrap() - 5.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn arrival_rate_counts_prints_in_the_window() {
+        let (mut a, t0) = (ArrivalRate::new(Duration::from_secs(2)), Instant::now());
+        let at = |ms| t0 + Duration::from_millis(ms);
+        a.advance(at(0));
+        for ms in [500, 1000, 1500] {
+            a.on_trade(at(ms));
+        }
+        a.advance(at(1900));
+        assert_eq!(a.rate(), None, "less than a window observed");
+        a.advance(at(2000));
+        assert_eq!(a.rate(), Some(1.5));
+        // the print at 0.5 s is now 2.1 s old
+        a.advance(at(2600));
+        assert_eq!(a.rate(), Some(1.0));
+        a.advance(at(10_000));
+        assert_eq!(a.rate(), Some(0.0));
+    }
+}
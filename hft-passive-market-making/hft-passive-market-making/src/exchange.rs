@@ -1,62 +1,520 @@
-use crate::models::*;
-use rand::Rng;
+use crate::book::{self, BookId, Match, Owner};
+use crate::config::{FlowCfg, HedgeCfg};
+use crate::flow::{Agent, Flow};
+use hft_core::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tracing::{debug, info};
 
-/// very small, in-process limit-order-book
-pub struct Exchange {
-    md_tx:   mpsc::Sender<Tick>,
-    fill_tx: mpsc::Sender<Fill>,
-    mid_px:  f64,
+/// price levels of simulated liquidity kept on each side
+const SIM_LEVELS: usize = 5;
+
+/// A second instrument quoted at a fixed spread around a fair value that
+/// moves with ours; it only takes marketable orders.
+struct HedgeLeg {
+    symbol:      String,
+    fair_px:     f64,
+    spread:      f64,
+    correlation: f64,
+}
+
+/// One simulated symbol: its book, the crowd quoting it and the traders
+/// hitting it.
+struct Listing {
+    symbol:  String,
+    book:    book::Book,
+    tick_sz: f64,
+    /// latent fair value the simulated crowd quotes around
+    fair_px: f64,
+    /// distance from fair to the crowd's best bid / ask
     spread:  f64,
+    /// fair-value moves of the coming steps, next first; informed traders
+    /// see them before they happen
+    moves:   VecDeque<f64>,
+    /// the crowd's market orders
+    flow:    Flow,
+    /// our live orders: client id → (book id, side)
+    live:    HashMap<OrderId, (BookId, Side)>,
+    /// book id → client id, to attribute maker fills
+    by_book: HashMap<BookId, OrderId>,
+    /// executions since the last step, printed with the next tick
+    tape:    Vec<Trade>,
+}
+
+/// in-process limit-order-book venue with simulated liquidity and order
+/// flow on one or more symbols
+pub struct Exchange {
+    md_tx:    mpsc::Sender<MarketData>,
+    er_tx:    mpsc::Sender<ExecReport>,
+    /// listed symbols; the first is the one a hedge leg follows
+    listings: Vec<Listing>,
+    /// order flow each listing gets
+    flow:     FlowCfg,
+    /// charges our fills, on every symbol
+    fees:     FeeMeter,
+    /// correlated instrument to hedge on, if any
+    hedge:    Option<HedgeLeg>,
+    /// sole source of randomness, so a seed replays the same session
+    rng:      StdRng,
+    /// session time of the current step, stamped on fills
+    now:      Instant,
+    /// session time of the previous step
+    last_step: Option<Instant>,
 }
 
 impl Exchange {
-    pub fn new(md_tx: mpsc::Sender<Tick>,
-               fill_tx: mpsc::Sender<Fill>,
-               start_mid: f64) -> Self
+    pub fn new(md_tx: mpsc::Sender<MarketData>,
+               er_tx: mpsc::Sender<ExecReport>,
+               symbol: &str,
+               start_mid: f64,
+               tick_sz: f64,
+               seed: u64) -> Self
     {
-        Self { md_tx, fill_tx, mid_px: start_mid, spread: 0.5 }
+        Self {
+            md_tx,
+            er_tx,
+            listings: Vec::new(),
+            flow: FlowCfg::default(),
+            fees: FeeMeter::default(),
+            hedge: None,
+            rng: StdRng::seed_from_u64(seed),
+            now: Instant::now(),
+            last_step: None,
+        }
+        .list(symbol, start_mid, tick_sz)
+    }
+
+    /// Also list `symbol`, with its own book, crowd and order flow.
+    pub fn list(mut self, symbol: &str, start_mid: f64, tick_sz: f64) -> Self {
+        self.listings.push(Listing {
+            symbol: symbol.to_string(),
+            book: book::Book::new(tick_sz),
+            tick_sz,
+            fair_px: start_mid,
+            spread: 0.5,
+            moves: VecDeque::new(),
+            flow: Flow::new(self.flow.clone()),
+            live: HashMap::new(),
+            by_book: HashMap::new(),
+            tape: Vec::new(),
+        });
+        self
     }
 
-    /// runs until *every* sender/receiver is dropped
-    pub async fn run(mut self, tick_ms: u64, mut od_rx: mpsc::Receiver<Order>) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_millis(tick_ms));
+    /// Charge our fills with `fees` (none by default).
+    pub fn fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = FeeMeter::new(fees);
+        self
+    }
+
+    /// Also list the hedge instrument of `cfg`, starting at the first
+    /// symbol's price.
+    pub fn hedge(mut self, cfg: &HedgeCfg) -> Self {
+        self.hedge = Some(HedgeLeg {
+            symbol:      cfg.symbol.clone(),
+            fair_px:     self.listings[0].fair_px,
+            spread:      cfg.spread,
+            correlation: cfg.correlation,
+        });
+        self
+    }
 
+    /// Replace the default noise-only order flow, on every symbol.
+    pub fn flow(mut self, cfg: FlowCfg) -> Self {
+        for l in &mut self.listings {
+            l.flow = Flow::new(cfg.clone());
+        }
+        self.flow = cfg;
+        self
+    }
+
+    /// runs until the clock ends the session or the strategy hangs up;
+    /// `done_rx` carries one message per market-data event once the
+    /// strategy has reacted to it
+    pub async fn run(mut self,
+                     mut clock: Clock,
+                     mut od_rx: mpsc::Receiver<OrderReq>,
+                     mut done_rx: mpsc::Receiver<()>)
+    {
         loop {
             tokio::select! {
-                _ = interval.tick() => {
-                    // ---- random-walk the mid ----
-                    let delta = {
-                        let mut rng = rand::thread_rng();
-                        rng.gen_range(-0.05..0.05)
-                    };
-                    self.mid_px += delta;
-
-                    let tick = Tick {
-                        bid: self.mid_px - self.spread,
-                        ask: self.mid_px + self.spread,
-                        ts:  std::time::Instant::now(),
-                    };
-                    if self.md_tx.send(tick).await.is_err() {
+                // requests already queued are handled before the next tick
+                biased;
+
+                Some(req) = od_rx.recv() => {
+                    let reports = self.on_request(req);
+                    if self.publish(reports).await.is_err() {
                         break;
                     }
                 }
 
-                Some(order) = od_rx.recv() => {
-                    let price_ok = match order.side {
-                        Side::Buy  => order.px >= self.mid_px + self.spread,
-                        Side::Sell => order.px <= self.mid_px - self.spread,
-                    };
-                    if price_ok {
-                        let fill = Fill { px: self.mid_px, qty: order.qty, side: order.side };
-                        if self.fill_tx.send(fill).await.is_err() {
-                            break;
+                now = clock.tick() => {
+                    // ticks carry session time, not wall time
+                    let Some(now) = now else { break };
+                    self.now = now;
+
+                    // ---- random-walk fair value, re-seed crowd, run flow ----
+                    let reports = self.step();
+                    if self.publish(reports).await.is_err() {
+                        break;
+                    }
+
+                    // per symbol the tape, then top of book, then the depth
+                    // snapshot behind it
+                    let mut events = Vec::new();
+                    for l in &mut self.listings {
+                        let mut prints = std::mem::take(&mut l.tape);
+                        for t in &mut prints {
+                            t.ts = now;
                         }
+                        let tick = Tick {
+                            symbol: l.symbol.clone(),
+                            bid:    l.book.best_bid().unwrap_or(l.fair_px - l.spread),
+                            ask:    l.book.best_ask().unwrap_or(l.fair_px + l.spread),
+                            ts:     now,
+                        };
+                        let depth = Book {
+                            symbol:  l.symbol.clone(),
+                            bid_vol: l.book.depth(Side::Buy),
+                            ask_vol: l.book.depth(Side::Sell),
+                            mid:     tick.mid(),
+                            ts:      now,
+                        };
+                        events.extend(prints.into_iter().map(MarketData::Trade));
+                        events.extend([MarketData::Tick(tick), MarketData::Book(depth)]);
+                    }
+                    events.extend(self.hedge_tick(now).map(MarketData::Tick));
+                    let n = events.len();
+                    let mut sent = true;
+                    for md in events {
+                        sent = sent && self.md_tx.send(md).await.is_ok();
+                    }
+                    if !sent || !self.settle(n, &mut od_rx, &mut done_rx).await {
+                        break;
                     }
                 }
 
                 else => break,
             }
         }
+        info!("session over after {} ticks", clock.ticks());
+        for l in &self.listings {
+            for agent in Agent::ALL {
+                let s = l.flow.stats(agent);
+                info!("FLOW {} {agent} orders={} qty={:.1} vs_us={:.1}", l.symbol, s.orders, s.qty, s.vs_us);
+            }
+        }
+    }
+
+    /// Lockstep with the strategy: wait until it has reacted to the `n`
+    /// events just published, then take in everything it sent before
+    /// stepping again. This keeps a seeded run identical however fast
+    /// either side is.
+    async fn settle(&mut self,
+                    n: usize,
+                    od_rx: &mut mpsc::Receiver<OrderReq>,
+                    done_rx: &mut mpsc::Receiver<()>) -> bool
+    {
+        for _ in 0..n {
+            if done_rx.recv().await.is_none() {
+                return false;
+            }
+        }
+        while let Ok(req) = od_rx.try_recv() {
+            let reports = self.on_request(req);
+            if self.publish(reports).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn on_request(&mut self, req: OrderReq) -> Vec<ExecReport> {
+        let (fees, now) = (&mut self.fees, self.now);
+        match req {
+            OrderReq::New(order) if self.hedge.as_ref().is_some_and(|h| h.symbol == order.symbol) => {
+                self.hedge_order(order)
+            }
+
+            OrderReq::New(order) => {
+                let Some(l) = self.listings.iter_mut().find(|l| l.symbol == order.symbol) else {
+                    return vec![reject(order.id, "unknown symbol")];
+                };
+                if l.live.contains_key(&order.id) {
+                    return vec![reject(order.id, "duplicate order id")];
+                }
+                if order.qty <= 0.0 {
+                    return vec![reject(order.id, "non-positive qty")];
+                }
+                let mut reports = vec![ExecReport::NewOrderAck { id: order.id }];
+                reports.extend(l.enter(order, fees, now));
+                reports
+            }
+
+            OrderReq::Cancel { id } => {
+                let live = self.listings.iter_mut().find_map(|l| {
+                    let (book_id, _) = l.live.remove(&id)?;
+                    Some((l, book_id))
+                });
+                match live {
+                    Some((l, book_id)) => {
+                        l.book.cancel(book_id);
+                        l.by_book.remove(&book_id);
+                        vec![ExecReport::CancelAck { id }]
+                    }
+                    None => vec![reject(id, "unknown or closed order")],
+                }
+            }
+
+            OrderReq::Replace { id, px, qty } => {
+                let Some(l) = self.listings.iter_mut().find(|l| l.live.contains_key(&id)) else {
+                    return vec![reject(id, "unknown or closed order")];
+                };
+                let (book_id, side) = l.live[&id];
+                if qty <= 0.0 {
+                    return vec![reject(id, "non-positive qty")];
+                }
+                let ack = ExecReport::ReplaceAck { id, px, qty };
+
+                // same price, smaller size → amend in place and keep priority
+                let open = l.book.open_qty(book_id).unwrap_or(0.0);
+                let same_px = l.book.price_of(book_id)
+                    .is_some_and(|p| l.book.to_tick(side, p) == l.book.to_tick(side, px));
+                if same_px && qty <= open && l.book.reduce(book_id, qty) {
+                    return vec![ack];
+                }
+
+                l.book.cancel(book_id);
+                l.by_book.remove(&book_id);
+                l.live.remove(&id);
+                let mut reports = vec![ack];
+                let order = Order::limit(id, l.symbol.clone(), side, px, qty);
+                reports.extend(l.enter(order, fees, now));
+                reports
+            }
+        }
+    }
+
+    /// Top of book of the hedge instrument, on the first symbol's tick grid.
+    fn hedge_tick(&self, now: Instant) -> Option<Tick> {
+        let h = self.hedge.as_ref()?;
+        let tick_sz = self.listings[0].tick_sz;
+        Some(Tick {
+            symbol: h.symbol.clone(),
+            bid:    ((h.fair_px - h.spread / 2.0) / tick_sz).floor() * tick_sz,
+            ask:    ((h.fair_px + h.spread / 2.0) / tick_sz).ceil() * tick_sz,
+            ts:     now,
+        })
+    }
+
+    /// Fill a marketable order on the hedge instrument at its touch.
+    fn hedge_order(&mut self, order: Order) -> Vec<ExecReport> {
+        let Some(touch) = self.hedge_tick(self.now) else { return Vec::new() };
+        let (px, marketable) = match order.side {
+            Side::Buy  => (touch.ask, order.px.is_none_or(|p| p >= touch.ask)),
+            Side::Sell => (touch.bid, order.px.is_none_or(|p| p <= touch.bid)),
+        };
+        if order.qty <= 0.0 || !marketable {
+            return vec![reject(order.id, "hedge leg takes marketable orders only")];
+        }
+        vec![
+            ExecReport::NewOrderAck { id: order.id },
+            ExecReport::Fill(self.fees.charge(Fill {
+                id: order.id, symbol: order.symbol, side: order.side, px, qty: order.qty,
+                leaves: 0.0, liquidity: Liquidity::Taker, fee: 0.0, ts: self.now,
+            })),
+        ]
+    }
+
+    /// One simulator step on every symbol: move fair value, refresh the
+    /// crowd's ladder and let the crowd's market orders sweep the book.
+    fn step(&mut self) -> Vec<ExecReport> {
+        let dt = self.last_step.map_or(0.0, |t| self.now.saturating_duration_since(t).as_secs_f64());
+        self.last_step = Some(self.now);
+        let mut reports = Vec::new();
+        for (i, l) in self.listings.iter_mut().enumerate() {
+            let step = l.advance(&mut self.rng);
+            if let (0, Some(h)) = (i, &mut self.hedge) {
+                // same size of move, `correlation` of it shared with ours
+                let own: f64 = self.rng.random_range(-0.05..0.05);
+                h.fair_px += h.correlation * step + (1.0 - h.correlation.powi(2)).sqrt() * own;
+            }
+            reports.extend(l.trade(&mut self.rng, dt, &mut self.fees, self.now));
+        }
+        reports
+    }
+
+    async fn publish(&self, reports: Vec<ExecReport>) -> Result<(), mpsc::error::SendError<ExecReport>> {
+        for r in reports {
+            self.er_tx.send(r).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Listing {
+    /// Cross what we can, rest the remainder at the back of its level.
+    /// Market orders never rest: whatever the book cannot fill is dropped.
+    fn enter(&mut self, order: Order, fees: &mut FeeMeter, now: Instant) -> Vec<ExecReport> {
+        let (rest, matches) = match order.px {
+            Some(px) => self.book.limit(Owner::Ours, order.side, px, order.qty),
+            None     => (None, self.book.market(order.side, order.qty)),
+        };
+
+        let mut left = order.qty;
+        let mut reports = Vec::new();
+        for (i, m) in matches.iter().enumerate() {
+            left -= m.qty;
+            // an unrested remainder is dropped with the last fill
+            let last = i + 1 == matches.len() && rest.is_none();
+            reports.push(ExecReport::Fill(fees.charge(Fill {
+                id: order.id,
+                symbol: self.symbol.clone(),
+                side: order.side,
+                px: m.px,
+                qty: m.qty,
+                leaves: if last { 0.0 } else { left.max(0.0) },
+                liquidity: Liquidity::Taker,
+                fee: 0.0,
+                ts: now,
+            })));
+        }
+        reports.extend(self.our_maker_fills(&matches, fees, now));
+        self.tape.extend(matches.iter().map(|m| print(&self.symbol, order.side, m)));
+
+        if let Some(book_id) = rest {
+            debug!("REST #{} {:?} px={:.2} qty={:.2} ahead={:.2}",
+                   order.id, order.side, order.px.unwrap_or_default(), left,
+                   self.book.queue_ahead(book_id).unwrap_or(0.0));
+            self.live.insert(order.id, (book_id, order.side));
+            self.by_book.insert(book_id, order.id);
+        }
+        reports
+    }
+
+    /// Random-walk fair value one step; returns the move.
+    fn advance(&mut self, rng: &mut StdRng) -> f64 {
+        while self.moves.len() <= self.flow.horizon() {
+            self.moves.push_back(rng.random_range(-0.05..0.05));
+        }
+        let step = self.moves.pop_front().unwrap_or_default();
+        self.fair_px += step;
+        step
+    }
+
+    /// Re-seed the crowd's ladder around fair value and run the `dt`
+    /// seconds of market orders since the last step.
+    fn trade(&mut self, rng: &mut StdRng, dt: f64, fees: &mut FeeMeter, now: Instant) -> Vec<ExecReport> {
+        // drop crowd orders that are now through fair value or too deep
+        let (fair, spread, band) = (self.fair_px, self.spread, SIM_LEVELS as f64 * self.tick_sz);
+        self.book.retain(|owner, side, px| {
+            if owner == Owner::Ours { return true; }
+            let dist = match side {
+                Side::Buy  => fair - px,
+                Side::Sell => px - fair,
+            };
+            dist >= spread - 1e-9 && dist < spread + band
+        });
+
+        // top up each crowd level; a crowd quote may cross our stale quote
+        let mut matches = Vec::new();
+        for k in 0..SIM_LEVELS {
+            let off = spread + k as f64 * self.tick_sz;
+            for (side, px) in [(Side::Buy, fair - off), (Side::Sell, fair + off)] {
+                if self.book.level_qty(side, px) < 1.0 {
+                    let qty = rng.random_range(1.0..5.0);
+                    let crossed = self.book.limit(Owner::Sim, side, px, qty).1;
+                    self.tape.extend(crossed.iter().map(|m| print(&self.symbol, side, m)));
+                    matches.extend(crossed);
+                }
+            }
+        }
+
+        // market orders from the noise, momentum and informed traders
+        let mid = match (self.book.best_bid(), self.book.best_ask()) {
+            (Some(bid), Some(ask)) => (bid + ask) / 2.0,
+            _ => fair,
+        };
+        let ahead: f64 = self.moves.iter().sum();
+        for (agent, side, qty) in self.flow.orders(rng, dt, mid, ahead) {
+            let swept = self.book.market(side, qty);
+            let ours = swept.iter().filter(|m| m.maker_owner == Owner::Ours).map(|m| m.qty).sum();
+            self.flow.record(agent, qty, ours);
+            self.tape.extend(swept.iter().map(|m| print(&self.symbol, side, m)));
+            matches.extend(swept);
+        }
+
+        self.our_maker_fills(&matches, fees, now)
+    }
+
+    /// Fills for executions where one of our resting orders was the maker.
+    fn our_maker_fills(&mut self, matches: &[Match], fees: &mut FeeMeter, now: Instant) -> Vec<ExecReport> {
+        let ours = || matches.iter().filter(|m| m.maker_owner == Owner::Ours);
+        // an order may be hit several times in one step: count its leaves
+        // down from what it had before the first of them
+        let mut open: HashMap<BookId, f64> = HashMap::new();
+        for m in ours() {
+            *open.entry(m.maker_id)
+                .or_insert_with(|| self.book.open_qty(m.maker_id).unwrap_or(0.0)) += m.qty;
+        }
+        let mut reports = Vec::new();
+        for m in ours() {
+            let Some(&id) = self.by_book.get(&m.maker_id) else { continue };
+            let left = open.get_mut(&m.maker_id).expect("counted above");
+            *left -= m.qty;
+            let leaves = if *left <= 1e-9 { 0.0 } else { *left };
+            reports.push(ExecReport::Fill(fees.charge(Fill {
+                id, symbol: self.symbol.clone(), side: m.maker_side,
+                px: m.px, qty: m.qty, leaves, liquidity: Liquidity::Maker, fee: 0.0, ts: now,
+            })));
+            // fully-filled orders are gone from the book
+            if leaves == 0.0 {
+                self.by_book.remove(&m.maker_id);
+                self.live.remove(&id);
+            }
+        }
+        reports
+    }
+}
+
+fn reject(id: OrderId, reason: &str) -> ExecReport {
+    ExecReport::Reject { id, reason: reason.into() }
+}
+
+/// A tape print for one match; stamped when published.
+fn print(symbol: &str, aggressor: Side, m: &Match) -> Trade {
+    Trade { symbol: symbol.to_string(), px: m.px, qty: m.qty, side: aggressor, ts: Instant::now() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_swept_twice_in_one_step_fills_twice() {
+        let (md_tx, _md_rx) = mpsc::channel(1);
+        let (er_tx, _er_rx) = mpsc::channel(1);
+        let mut ex = Exchange::new(md_tx, er_tx, "XYZ", 100.0, 0.01, 7);
+        let now = ex.now;
+        let listing = &mut ex.listings[0];
+        let rested = listing.enter(Order::limit(1, "XYZ", Side::Sell, 100.5, 3.0), &mut ex.fees, now);
+        assert!(rested.is_empty());
+
+        // two market buys in the same step, both against our ask
+        let mut matches = listing.book.market(Side::Buy, 1.0);
+        matches.extend(listing.book.market(Side::Buy, 2.0));
+        let fills: Vec<(f64, f64)> = listing.our_maker_fills(&matches, &mut ex.fees, now)
+            .into_iter()
+            .map(|er| match er {
+                ExecReport::Fill(f) => (f.qty, f.leaves),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+
+        assert_eq!(fills, vec![(1.0, 2.0), (2.0, 0.0)]);
+        assert!(listing.live.is_empty() && listing.by_book.is_empty());
     }
 }
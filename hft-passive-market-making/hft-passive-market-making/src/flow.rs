@@ -0,0 +1,204 @@
+//! Simulated traders that send market orders to the [`Exchange`](crate::exchange::Exchange):
+//!
+//! * **noise** — random side, no view;
+//! * **momentum** — trade with the mid's move over the last few ticks;
+//! * **informed** — know where fair value goes over the next few ticks and
+//!   trade ahead of it. They are the adverse selection a quote has to price.
+//!
+//! Each population arrives as a Poisson process in session time, so the
+//! mix is set by their rates.
+
+use crate::config::{AgentCfg, FlowCfg};
+use hft_core::Side;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agent { Noise, Momentum, Informed }
+
+impl Agent {
+    pub const ALL: [Agent; 3] = [Agent::Noise, Agent::Momentum, Agent::Informed];
+}
+
+impl fmt::Display for Agent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Agent::Noise    => "noise",
+            Agent::Momentum => "momentum",
+            Agent::Informed => "informed",
+        })
+    }
+}
+
+/// What one population sent, and how much of it we filled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AgentStats {
+    pub orders:  u64,
+    pub qty:     f64,
+    /// quantity executed against our resting quotes
+    pub vs_us:   f64,
+}
+
+pub struct Flow {
+    cfg:   FlowCfg,
+    /// recent mids, oldest first, `momentum_lookback + 1` at most
+    mids:  VecDeque<f64>,
+    stats: [AgentStats; 3],
+}
+
+impl Flow {
+    pub fn new(cfg: FlowCfg) -> Self {
+        Self { cfg, mids: VecDeque::new(), stats: Default::default() }
+    }
+
+    /// Ticks of future fair-value moves the informed traders are told.
+    pub fn horizon(&self) -> usize { self.cfg.informed_horizon }
+
+    /// Market orders arriving over the `dt` seconds just elapsed, given the
+    /// current `mid` and the fair-value move `ahead` over the horizon.
+    pub fn orders(&mut self, rng: &mut impl Rng, dt: f64, mid: f64, ahead: f64) -> Vec<(Agent, Side, f64)> {
+        self.mids.push_back(mid);
+        if self.mids.len() > self.cfg.momentum_lookback + 1 {
+            self.mids.pop_front();
+        }
+        let trend = match self.mids.front() {
+            Some(first) if self.mids.len() > self.cfg.momentum_lookback => mid - first,
+            _ => 0.0,
+        };
+
+        let mut orders = Vec::new();
+        for agent in Agent::ALL {
+            let cfg = self.agent_cfg(agent);
+            for _ in 0..poisson(rng, cfg.rate * dt) {
+                let side = match agent {
+                    Agent::Noise    => Some(if rng.random_bool(0.5) { Side::Buy } else { Side::Sell }),
+                    Agent::Momentum => direction(trend),
+                    Agent::Informed => direction(ahead),
+                };
+                let qty = rng.random_range(cfg.min_qty..=cfg.max_qty);
+                if let Some(side) = side {
+                    orders.push((agent, side, qty));
+                }
+            }
+        }
+        orders
+    }
+
+    /// Book an order `agent` sent, `ours` of which filled our quotes.
+    pub fn record(&mut self, agent: Agent, qty: f64, ours: f64) {
+        let s = &mut self.stats[agent as usize];
+        s.orders += 1;
+        s.qty += qty;
+        s.vs_us += ours;
+    }
+
+    pub fn stats(&self, agent: Agent) -> AgentStats { self.stats[agent as usize] }
+
+    fn agent_cfg(&self, agent: Agent) -> AgentCfg {
+        match agent {
+            Agent::Noise    => self.cfg.noise,
+            Agent::Momentum => self.cfg.momentum,
+            Agent::Informed => self.cfg.informed,
+        }
+    }
+}
+
+/// Buy into a rise, sell into a fall, sit out a flat market.
+fn direction(move_: f64) -> Option<Side> {
+    if move_ > 1e-9 {
+        Some(Side::Buy)
+    } else if move_ < -1e-9 {
+        Some(Side::Sell)
+    } else {
+        None
+    }
+}
+
+/// Poisson(`lambda`) draw by multiplying uniforms (Knuth); `lambda` is the
+/// expected arrivals per step and stays small.
+fn poisson(rng: &mut impl Rng, lambda: f64) -> u32 {
+    if lambda <= 0.0 {
+        return 0;
+    }
+    let limit = (-lambda).exp();
+    let mut k = 0;
+    let mut p: f64 = rng.random();
+    while p > limit {
+        k += 1;
+        p *= rng.random::<f64>();
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const OFF: AgentCfg = AgentCfg { rate: 0.0, min_qty: 1.0, max_qty: 1.0 };
+    const BUSY: AgentCfg = AgentCfg { rate: 50.0, min_qty: 0.5, max_qty: 2.0 };
+
+    fn only(agent: Agent) -> Flow {
+        let on = |a| if a == agent { BUSY } else { OFF };
+        Flow::new(FlowCfg {
+            noise: on(Agent::Noise),
+            momentum: on(Agent::Momentum),
+            momentum_lookback: 2,
+            informed: on(Agent::Informed),
+            informed_horizon: 5,
+        })
+    }
+
+    fn sides(orders: &[(Agent, Side, f64)]) -> (usize, usize) {
+        let buys = orders.iter().filter(|o| o.1 == Side::Buy).count();
+        (buys, orders.len() - buys)
+    }
+
+    #[test]
+    fn noise_trades_both_ways_within_its_sizes() {
+        let (mut flow, mut rng) = (only(Agent::Noise), StdRng::seed_from_u64(1));
+        let orders: Vec<_> = (0..20).flat_map(|_| flow.orders(&mut rng, 1.0, 100.0, 0.0)).collect();
+        let (buys, sells) = sides(&orders);
+        assert!(buys > 300 && sells > 300, "{buys} buys, {sells} sells");
+        assert!(orders.iter().all(|&(a, _, q)| a == Agent::Noise && (0.5..=2.0).contains(&q)));
+        // 50 a second for 20 seconds
+        assert!((orders.len() as f64 - 1000.0).abs() < 100.0, "{}", orders.len());
+    }
+
+    #[test]
+    fn momentum_follows_the_trend_once_it_has_one() {
+        let (mut flow, mut rng) = (only(Agent::Momentum), StdRng::seed_from_u64(2));
+        assert!(flow.orders(&mut rng, 1.0, 100.0, 0.0).is_empty());
+        assert!(flow.orders(&mut rng, 1.0, 100.5, 0.0).is_empty(), "lookback not filled yet");
+        let up = flow.orders(&mut rng, 1.0, 101.0, 0.0);
+        assert!(!up.is_empty() && sides(&up).1 == 0);
+        // 100.5 → 101.0 → 100.0 is down over the lookback
+        let down = flow.orders(&mut rng, 1.0, 100.0, 0.0);
+        assert!(!down.is_empty() && sides(&down).0 == 0);
+        // 101.0 → 100.0 → 101.0 is flat
+        assert!(flow.orders(&mut rng, 1.0, 101.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn informed_trade_ahead_of_fair_value() {
+        let (mut flow, mut rng) = (only(Agent::Informed), StdRng::seed_from_u64(3));
+        assert_eq!(flow.horizon(), 5);
+        let up = flow.orders(&mut rng, 1.0, 100.0, 0.2);
+        assert!(!up.is_empty() && up.iter().all(|o| o.0 == Agent::Informed && o.1 == Side::Buy));
+        let down = flow.orders(&mut rng, 1.0, 100.0, -0.2);
+        assert!(!down.is_empty() && sides(&down).0 == 0);
+        assert!(flow.orders(&mut rng, 1.0, 100.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn record_books_orders_per_agent() {
+        let mut flow = only(Agent::Noise);
+        flow.record(Agent::Informed, 2.0, 1.5);
+        flow.record(Agent::Informed, 1.0, 0.0);
+        let s = flow.stats(Agent::Informed);
+        assert_eq!((s.orders, s.qty, s.vs_us), (2, 3.0, 1.5));
+        assert_eq!(flow.stats(Agent::Noise).orders, 0);
+    }
+}
@@ -0,0 +1,141 @@
+//! Inventory hedger: once the quoting inventory, net of what is already
+//! hedged, drifts past a threshold, take liquidity on a correlated
+//! instrument to bring the net exposure back to zero.
+//!
+//! ```text
+//! exposure = inventory + hedge position / ratio
+//! ```
+//!
+//! The hedge keeps its own [`Position`], so its PnL and fees are reported
+//! apart from the quoting book's.
+
+use crate::config::HedgeCfg;
+use hft_core::{Fill, Order, OrderId, OrderIds, Position, Side, Tick};
+
+pub struct Hedger {
+    cfg: HedgeCfg,
+    pos: Position,
+    /// hedge orders in flight, with their signed open quantity
+    open: Vec<(OrderId, f64)>,
+}
+
+impl Hedger {
+    pub fn new(cfg: HedgeCfg) -> Self {
+        Self { cfg, pos: Position::default(), open: Vec::new() }
+    }
+
+    pub fn symbol(&self) -> &str { &self.cfg.symbol }
+
+    pub fn on_tick(&mut self, tick: &Tick) {
+        self.pos.mark(tick.mid());
+    }
+
+    /// Quoting inventory still unhedged, counting orders in flight.
+    pub fn exposure(&self, inv: f64) -> f64 {
+        inv + self.held() / self.cfg.ratio
+    }
+
+    /// A market order on the hedge instrument when `inv` leaves too much
+    /// exposure.
+    pub fn order(&self, inv: f64, ids: &mut OrderIds) -> Option<Order> {
+        let exposure = self.exposure(inv);
+        if exposure.abs() <= self.cfg.threshold {
+            return None;
+        }
+        let side = if exposure > 0.0 { Side::Sell } else { Side::Buy };
+        Some(Order::market(ids.next(), &self.cfg.symbol, side, exposure.abs() * self.cfg.ratio))
+    }
+
+    /// A market order closing the hedge position, once our own inventory
+    /// is being flattened.
+    pub fn flatten(&self, ids: &mut OrderIds) -> Option<Order> {
+        let held = self.held();
+        if held.abs() <= 1e-9 {
+            return None;
+        }
+        let side = if held > 0.0 { Side::Sell } else { Side::Buy };
+        Some(Order::market(ids.next(), &self.cfg.symbol, side, held.abs()))
+    }
+
+    /// Hedge position plus orders in flight.
+    fn held(&self) -> f64 {
+        self.pos.qty() + self.open.iter().map(|(_, qty)| qty).sum::<f64>()
+    }
+
+    /// A hedge order went out to the venue.
+    pub fn on_sent(&mut self, o: &Order) {
+        self.open.push((o.id, o.side.sign() * o.qty));
+    }
+
+    pub fn on_fill(&mut self, f: &Fill) {
+        self.pos.on_fill(f.side, f.qty, f.px, f.fee);
+        if let Some(o) = self.open.iter_mut().find(|(id, _)| *id == f.id) {
+            o.1 -= f.side.sign() * f.qty;
+        }
+        if f.leaves <= 0.0 {
+            self.done(f.id);
+        }
+    }
+
+    /// The venue is done with hedge order `id` (fully filled or rejected):
+    /// whatever did not fill is no longer in flight.
+    pub fn done(&mut self, id: OrderId) {
+        self.open.retain(|(o, _)| *o != id);
+    }
+
+    pub fn owns(&self, id: OrderId) -> bool {
+        self.open.iter().any(|(o, _)| *o == id)
+    }
+
+    pub fn position(&self) -> &Position { &self.pos }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hft_core::Liquidity;
+    use std::time::Instant;
+
+    fn hedger() -> Hedger {
+        Hedger::new(HedgeCfg { symbol: "SIMFUT".into(), ratio: 2.0, threshold: 4.0, correlation: 0.9, spread: 0.02 })
+    }
+
+    fn fill(id: OrderId, side: Side, qty: f64, leaves: f64) -> Fill {
+        Fill { id, symbol: "SIMFUT".into(), side, px: 50.0, qty, leaves, liquidity: Liquidity::Taker, fee: 0.0, ts: Instant::now() }
+    }
+
+    #[test]
+    fn hedges_past_the_threshold_at_the_ratio() {
+        let (h, mut ids) = (hedger(), OrderIds::default());
+        assert!(h.order(4.0, &mut ids).is_none());
+        assert!(h.order(-4.0, &mut ids).is_none());
+
+        let o = h.order(5.0, &mut ids).unwrap();
+        assert_eq!((o.symbol.as_str(), o.side, o.qty, o.px), ("SIMFUT", Side::Sell, 10.0, None));
+        let o = h.order(-6.0, &mut ids).unwrap();
+        assert_eq!((o.side, o.qty), (Side::Buy, 12.0));
+    }
+
+    #[test]
+    fn orders_in_flight_count_as_hedged() {
+        let (mut h, mut ids) = (hedger(), OrderIds::default());
+        let o = h.order(5.0, &mut ids).unwrap();
+        h.on_sent(&o);
+        assert_eq!(h.exposure(5.0), 0.0);
+        assert!(h.order(5.0, &mut ids).is_none(), "no second hedge while the first is working");
+
+        // 4 of 10 fill and the venue kills the rest
+        h.on_fill(&fill(o.id, Side::Sell, 4.0, 6.0));
+        assert!(h.owns(o.id) && h.exposure(5.0) == 0.0);
+        h.done(o.id);
+        assert!(!h.owns(o.id));
+        assert_eq!((h.position().qty(), h.exposure(5.0)), (-4.0, 3.0));
+
+        // inventory swung short: buy back the hedge and go long beyond it
+        let o = h.order(-6.0, &mut ids).unwrap();
+        assert_eq!((o.side, o.qty), (Side::Buy, 16.0));
+
+        let f = h.flatten(&mut ids).unwrap();
+        assert_eq!((f.side, f.qty), (Side::Buy, 4.0));
+    }
+}
@@ -0,0 +1,73 @@
+//! Quote ladder: price offsets and sizes of the levels a side is quoted
+//! at, measured outward from the model's bid or ask.
+
+use crate::config::{MmCfg, Spacing};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Level {
+    /// distance behind the top level (0 for the top itself)
+    pub offset: f64,
+    pub qty:    f64,
+}
+
+/// Levels per side for `cfg`; a single level of `size` without a ladder.
+pub fn levels(cfg: &MmCfg) -> Vec<Level> {
+    let Some(ladder) = &cfg.ladder else {
+        return vec![Level { offset: 0.0, qty: cfg.size }];
+    };
+    let mut offset = 0.0;
+    let mut gap = ladder.step;
+    (0..ladder.levels)
+        .map(|k| {
+            let qty = ladder.sizes.get(k)
+                .or(ladder.sizes.last())
+                .copied()
+                .unwrap_or(cfg.size);
+            let level = Level { offset, qty };
+            offset += gap;
+            if ladder.spacing == Spacing::Geometric {
+                gap *= ladder.ratio;
+            }
+            level
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LadderCfg;
+    use figment::providers::{Format, Toml};
+
+    fn cfg(ladder: Option<LadderCfg>) -> MmCfg {
+        let cfg = MmCfg::extract(figment::Figment::from(Toml::string(include_str!("../Config.toml")))).unwrap();
+        MmCfg { ladder, size: 1.5, ..cfg }
+    }
+
+    fn ladder(spacing: Spacing, sizes: Vec<f64>) -> Option<LadderCfg> {
+        Some(LadderCfg { levels: 4, spacing, step: 0.05, ratio: 2.0, sizes })
+    }
+
+    fn offsets_and_sizes(levels: &[Level]) -> (Vec<f64>, Vec<f64>) {
+        levels.iter().map(|l| ((l.offset * 1e6).round() / 1e6, l.qty)).unzip()
+    }
+
+    #[test]
+    fn no_ladder_is_one_level_of_size() {
+        let l = levels(&cfg(None));
+        assert_eq!(offsets_and_sizes(&l), (vec![0.0], vec![1.5]));
+    }
+
+    #[test]
+    fn linear_levels_repeat_the_last_size() {
+        let l = levels(&cfg(ladder(Spacing::Linear, vec![1.0, 2.0])));
+        assert_eq!(offsets_and_sizes(&l), (vec![0.0, 0.05, 0.1, 0.15], vec![1.0, 2.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn geometric_gaps_grow_and_empty_sizes_take_size() {
+        let l = levels(&cfg(ladder(Spacing::Geometric, Vec::new())));
+        // gaps 0.05, 0.1, 0.2
+        assert_eq!(offsets_and_sizes(&l), (vec![0.0, 0.05, 0.15, 0.35], vec![1.5; 4]));
+    }
+}
@@ -0,0 +1,16 @@
+//! Inventory-skew market maker, on one or more symbols, and the price-time
+//! priority venue it quotes on.
+
+pub mod avellaneda;
+pub mod book;
+pub mod config;
+pub mod estimators;
+pub mod exchange;
+pub mod flow;
+pub mod hedge;
+pub mod ladder;
+pub mod liquidation;
+pub mod markout;
+pub mod portfolio;
+pub mod risk;
+pub mod strategy;
@@ -0,0 +1,189 @@
+//! End-of-session liquidation: at `stop_secs` of session time quoting
+//! stops and the inventory is worked down with child orders every
+//! `slice_ms`, either
+//!
+//! * **TWAP** — market orders of an equal share of what is left over the
+//!   slices remaining before the deadline, or
+//! * **IOC** — the whole remainder as a limit up to `max_slip_ticks`
+//!   through the touch, cancelled straight after so nothing rests;
+//!
+//! and whatever is left at the deadline is flattened with market orders.
+//! The cost is measured against the mid when quoting stopped:
+//!
+//! ```text
+//! cost = Σ sign · qty · (px − arrival mid) + fees        sign = +1 buy, −1 sell
+//! ```
+
+use crate::config::{LiqMethod, LiquidationCfg};
+use hft_core::{Fill, Order, OrderId, OrderIds, OrderReq, Side, Tick};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    Quoting,
+    Working { next_slice: Instant },
+    Flat { at: Instant },
+}
+
+pub struct Liquidator {
+    cfg:       LiquidationCfg,
+    phase:     Phase,
+    /// session start, stop time and flatten deadline
+    start:     Option<Instant>,
+    stop:      Option<Instant>,
+    deadline:  Option<Instant>,
+    /// every child order, so its acks, fills and rejects are recognised
+    orders:    Vec<OrderId>,
+    /// child orders of the hard flatten
+    flatten:   Vec<OrderId>,
+    start_inv: f64,
+    arrival:   f64,
+    filled:    f64,
+    slippage:  f64,
+    fees:      f64,
+    /// quantity filled by the flatten at the deadline
+    forced:    f64,
+}
+
+impl Liquidator {
+    pub fn new(cfg: LiquidationCfg) -> Self {
+        Self {
+            cfg,
+            phase: Phase::Quoting,
+            start: None,
+            stop: None,
+            deadline: None,
+            orders: Vec::new(),
+            flatten: Vec::new(),
+            start_inv: 0.0,
+            arrival: 0.0,
+            filled: 0.0,
+            slippage: 0.0,
+            fees: 0.0,
+            forced: 0.0,
+        }
+    }
+
+    /// Whether the flatten deadline has passed.
+    pub fn past_deadline(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|d| now >= d)
+    }
+
+    /// Child orders for inventory `inv` on this tick; `None` while the
+    /// session is still quoting.
+    pub fn on_tick(&mut self, tick: &Tick, inv: f64, tick_sz: f64, ids: &mut OrderIds) -> Option<Vec<OrderReq>> {
+        let now = tick.ts;
+        let start = *self.start.get_or_insert(now);
+        let stop = *self.stop.get_or_insert(start + Duration::from_secs_f64(self.cfg.stop_secs));
+        let deadline = *self.deadline.get_or_insert(stop + Duration::from_secs_f64(self.cfg.flatten_secs));
+
+        let next_slice = match self.phase {
+            Phase::Quoting if now < stop => return None,
+            Phase::Quoting => {
+                info!("LIQUIDATION {} start inv={inv:.2} mid={:.2} method={:?}", tick.symbol, tick.mid(), self.cfg.method);
+                self.start_inv = inv;
+                self.arrival = tick.mid();
+                now
+            }
+            Phase::Working { next_slice } => next_slice,
+            Phase::Flat { .. } if inv.abs() <= 1e-9 => return Some(Vec::new()),
+            // a late fill took us off flat: work the remainder down again
+            Phase::Flat { .. } => now,
+        };
+        if inv.abs() <= 1e-9 {
+            info!("LIQUIDATION {} flat after {:.1}s", tick.symbol, now.saturating_duration_since(stop).as_secs_f64());
+            self.phase = Phase::Flat { at: now };
+            return Some(Vec::new());
+        }
+
+        let side = if inv > 0.0 { Side::Sell } else { Side::Buy };
+        let slice = Duration::from_millis(self.cfg.slice_ms);
+        let mut reqs = Vec::new();
+        let mut next = next_slice;
+        if now >= deadline {
+            // hard flatten, again on every tick until we are flat
+            let id = ids.next();
+            self.flatten.push(id);
+            reqs.push(OrderReq::New(Order::market(id, &tick.symbol, side, inv.abs())));
+        } else if now >= next_slice {
+            next = next_slice + slice;
+            match self.cfg.method {
+                LiqMethod::Twap => {
+                    let left = deadline.saturating_duration_since(now).as_secs_f64();
+                    let slices = (left / slice.as_secs_f64()).ceil().max(1.0);
+                    reqs.push(OrderReq::New(Order::market(ids.next(), &tick.symbol, side, inv.abs() / slices)));
+                }
+                LiqMethod::Ioc => {
+                    let slip = self.cfg.max_slip_ticks * tick_sz;
+                    let px = match side {
+                        Side::Buy  => tick.ask + slip,
+                        Side::Sell => tick.bid - slip,
+                    };
+                    let id = ids.next();
+                    reqs.push(OrderReq::New(Order::limit(id, &tick.symbol, side, px, inv.abs())));
+                    reqs.push(OrderReq::Cancel { id });
+                }
+            }
+        }
+        self.phase = Phase::Working { next_slice: next };
+        self.orders.extend(reqs.iter().map(OrderReq::id));
+        Some(reqs)
+    }
+
+    pub fn owns(&self, id: OrderId) -> bool {
+        self.orders.contains(&id)
+    }
+
+    pub fn on_fill(&mut self, f: &Fill) {
+        self.filled += f.qty;
+        self.slippage += f.side.sign() * f.qty * (f.px - self.arrival);
+        self.fees += f.fee;
+        if self.flatten.contains(&f.id) {
+            self.forced += f.qty;
+        }
+    }
+
+    /// Outcome of the liquidation; `inv` is the inventory of `symbol` left
+    /// now.
+    pub fn log_report(&self, symbol: &str, inv: f64) {
+        let Some(stop) = self.stop else { return };
+        let flat = match self.phase {
+            Phase::Quoting => return info!("LIQUIDATION {symbol} not started: session ended before the stop"),
+            Phase::Working { .. } => "never".to_string(),
+            Phase::Flat { at } => format!("{:.1}s", at.saturating_duration_since(stop).as_secs_f64()),
+        };
+        info!("LIQUIDATION {symbol} start_inv={:.2} arrival_mid={:.2} filled={:.2} forced={:.2} residual={:.2} cost={:.2} (slippage={:.2} fees={:.2}) flat_after={}",
+              self.start_inv, self.arrival, self.filled, self.forced, inv,
+              self.slippage + self.fees, self.slippage, self.fees, flat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(ts: Instant) -> Tick {
+        Tick { symbol: "XYZ".into(), bid: 99.9, ask: 100.1, ts }
+    }
+
+    #[test]
+    fn inventory_after_flat_is_worked_down_again() {
+        let mut liq = Liquidator::new(LiquidationCfg {
+            stop_secs: 1.0, flatten_secs: 10.0, method: LiqMethod::Twap, slice_ms: 100, max_slip_ticks: 0.0,
+        });
+        let mut ids = OrderIds::default();
+        let t0 = Instant::now();
+        let at = |ms| tick(t0 + Duration::from_millis(ms));
+
+        assert!(liq.on_tick(&at(0), 0.0, 0.01, &mut ids).is_none());
+        assert!(liq.on_tick(&at(1_000), 0.0, 0.01, &mut ids).is_some_and(|r| r.is_empty()));
+        assert!(matches!(liq.phase, Phase::Flat { .. }));
+        assert!(liq.on_tick(&at(1_100), 0.0, 0.01, &mut ids).is_some_and(|r| r.is_empty()));
+
+        // a fill lands after we went flat
+        let reqs = liq.on_tick(&at(1_200), 2.0, 0.01, &mut ids).unwrap();
+        assert!(matches!(&reqs[..], [OrderReq::New(o)] if o.side == Side::Sell && o.qty > 0.0));
+        assert!(matches!(liq.phase, Phase::Working { .. }));
+    }
+}
@@ -1,58 +1,77 @@
-mod config;
-mod models;
-mod exchange;
-mod strategy;
-mod risk;
-
+use hft_core::{history, link, Clock, Engine, OrderReq, Performance, Recorder, Replay};
+use hft_passive_mm::{config::MmCfg, exchange, portfolio::Portfolio, risk};
+use std::time::Duration;
 use tracing::info;
-use tokio::sync::mpsc;
-use crate::models::*;
-use crate::config::MmCfg;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     let cfg = MmCfg::load()?;
+    let books = cfg.books();
+    for b in &books {
+        info!("quoting {} model={:?} half_spread={} size={}", b.symbol, b.model, b.half_spread, b.size);
+    }
 
     // -------- channels --------
-    let (md_tx, mut md_rx)   = mpsc::channel::<Tick>(1024);
-    let (fill_tx, mut fills) = mpsc::channel::<Fill>(1024);
-    let (od_tx, od_rx)       = mpsc::channel::<Order>(1024);
+    let (venue, engine) = link(1024);
 
-    // -------- spawn exchange sim --------
-    let ex  = exchange::Exchange::new(md_tx, fill_tx, 100.0);
-    tokio::spawn(ex.run(cfg.tick_ms, od_rx));
+    // -------- spawn exchange sim (or replay a recording) --------
+    let clock = Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks);
+    let origin = clock.start();
+    match (&cfg.replay, &cfg.data) {
+        (Some(path), _) => {
+            info!("replaying {path}");
+            let rp = Replay::open(path, venue.md_tx, venue.er_tx)?.fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, Some(path)) => {
+            info!("running on historical data {path}");
+            let mut syms: Vec<&str> = books.iter().map(|b| b.symbol.as_str()).collect();
+            syms.extend(cfg.hedge.as_ref().map(|h| h.symbol.as_str()));
+            let md = history::load(path, &syms, origin)?;
+            let rp = Replay::new(md, venue.md_tx, venue.er_tx).fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, None) => {
+            let mut ex = exchange::Exchange::new(venue.md_tx, venue.er_tx, &cfg.symbol, cfg.mid_px, cfg.tick_sz, cfg.seed);
+            for b in &books[1..] {
+                ex = ex.list(&b.symbol, b.mid_px, b.tick_sz);
+            }
+            ex = ex.flow(cfg.flow.clone()).fees(cfg.fees.clone());
+            if let Some(hedge) = &cfg.hedge {
+                ex = ex.hedge(hedge);
+            }
+            tokio::spawn(ex.run(clock, venue.od_rx, venue.done_rx));
+        }
+    }
 
     // -------- trading state --------
-    let mut mm   = strategy::InventoryMm::new(cfg.clone());
+    let pf       = Portfolio::new(&cfg);
     let mut risk = risk::Risk::new(cfg.clone());
 
-    // -------- event loop --------
-    loop {
-        tokio::select! {
-            Some(tick) = md_rx.recv() => {
-                let (bid, ask) = mm.quote(&tick);
-
-                for (px, side) in [(bid, Side::Buy), (ask, Side::Sell)] {
-                    let qty = cfg.size;
-                    let dir = if matches!(side, Side::Buy) { 1.0 } else { -1.0 };
-                    let inv_after = mm.inv() + dir * qty;
-                    let ord = Order { px, qty, side: side.clone() };
-
-                    if risk.allow(&ord, inv_after) {
-                        od_tx.send(ord).await?;
-                    }
-                }
-            }
-
-            Some(fill) = fills.recv() => {
-                mm.on_fill(&fill);
-                info!("FILL {:?} qty={:.0} px={:.2} inv={:.1} pnl={:.2}",
-                      fill.side, fill.qty, fill.px, mm.inv(), mm.pnl());
-            }
+    // -------- end-of-run report --------
+    let mut perf = Performance::new("hft-passive-mm", Duration::from_millis(cfg.report_bucket_ms));
+    if let Some(path) = &cfg.report_json {
+        perf = perf.json(path);
+    }
 
-            else => break,
+    // -------- event loop --------
+    let mut engine = Engine::new(pf, engine)
+        .risk(move |pf: &Portfolio, req: &OrderReq, now| risk.allow(pf, req, now))
+        .timer(Duration::from_secs(cfg.summary_secs))
+        .report(perf);
+    if let Some(path) = &cfg.record {
+        engine = engine.record(Recorder::create(path, origin)?);
+    }
+    let pf = engine.run().await;
+    for mm in pf.books() {
+        mm.log_summary();
+        mm.log_quoting();
+        mm.log_liquidation();
+        if let Some(markouts) = mm.markouts() {
+            markouts.finish();
         }
     }
+    pf.log_summary();
     Ok(())
 }
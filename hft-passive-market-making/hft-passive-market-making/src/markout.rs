@@ -0,0 +1,303 @@
+//! Adverse selection of our fills: how far the mid moved for or against
+//! each fill over fixed horizons of session time.
+//!
+//! ```text
+//! edge      = sign · (mid at the fill − px)
+//! markout_h = sign · (mid at fill + h − px)        sign = +1 buy, −1 sell
+//! ```
+//!
+//! Both are per contract in price units, like `half_spread`: the edge is the
+//! half-spread actually captured and a markout curve falling below it is the
+//! price of being picked off. "Mid at fill + h" is the first mid published
+//! at or after that time. Curves are quantity-weighted means, cut by side
+//! and by the inventory we held just before the fill.
+
+use crate::config::MarkoutCfg;
+use hft_core::{Fill, OrderId, Side};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+pub struct Markouts {
+    cfg:      MarkoutCfg,
+    symbol:   String,
+    horizons: Vec<Duration>,
+    /// latest mid and the session start
+    mid:      Option<f64>,
+    start:    Option<Instant>,
+    /// fills still waiting on a horizon, oldest first
+    open:     VecDeque<FillMarkout>,
+    done:     Vec<FillMarkout>,
+}
+
+/// One fill and its markouts so far (`None` until the horizon passed).
+#[derive(Debug, Clone)]
+struct FillMarkout {
+    ts:       Instant,
+    /// seconds into the session
+    at:       f64,
+    id:       OrderId,
+    side:     Side,
+    px:       f64,
+    qty:      f64,
+    /// our inventory before the fill
+    inv:      f64,
+    edge:     f64,
+    markouts: Vec<Option<f64>>,
+}
+
+impl Markouts {
+    pub fn new(cfg: MarkoutCfg, symbol: &str) -> Self {
+        let horizons = cfg.horizons_ms.iter().map(|&ms| Duration::from_millis(ms)).collect();
+        Self { cfg, symbol: symbol.to_string(), horizons, mid: None, start: None, open: VecDeque::new(), done: Vec::new() }
+    }
+
+    /// Latest mid of our symbol; settles every horizon that has now passed.
+    pub fn on_mid(&mut self, ts: Instant, mid: f64) {
+        self.start.get_or_insert(ts);
+        self.mid = Some(mid);
+        for f in self.open.iter_mut() {
+            for (m, h) in f.markouts.iter_mut().zip(&self.horizons) {
+                if m.is_none() && f.ts + *h <= ts {
+                    *m = Some(f.side.sign() * (mid - f.px));
+                }
+            }
+        }
+        while self.open.front().is_some_and(|f| f.markouts.iter().all(Option::is_some)) {
+            self.done.extend(self.open.pop_front());
+        }
+    }
+
+    /// `inv` is our inventory before `f`.
+    pub fn on_fill(&mut self, f: &Fill, inv: f64) {
+        let (Some(mid), Some(start)) = (self.mid, self.start) else { return };
+        self.open.push_back(FillMarkout {
+            ts:       f.ts,
+            at:       f.ts.saturating_duration_since(start).as_secs_f64(),
+            id:       f.id,
+            side:     f.side,
+            px:       f.px,
+            qty:      f.qty,
+            inv,
+            edge:     f.side.sign() * (mid - f.px),
+            markouts: vec![None; self.horizons.len()],
+        });
+    }
+
+    fn fills(&self) -> impl Iterator<Item = &FillMarkout> {
+        self.done.iter().chain(&self.open)
+    }
+
+    /// Index of the inventory bucket `inv` falls in.
+    fn bucket(&self, inv: f64) -> usize {
+        self.cfg.inv_buckets.iter().take_while(|&&e| inv >= e).count()
+    }
+
+    fn bucket_label(&self, k: usize) -> String {
+        let edges = &self.cfg.inv_buckets;
+        match (k.checked_sub(1).map(|i| edges[i]), edges.get(k)) {
+            (None, None)           => "all".into(),
+            (None, Some(hi))       => format!("inv < {hi}"),
+            (Some(lo), None)       => format!("inv >= {lo}"),
+            (Some(lo), Some(hi))   => format!("{lo} <= inv < {hi}"),
+        }
+    }
+
+    /// Markout curves of the session so far.
+    pub fn report(&self) -> MarkoutReport {
+        let curve = |label: String, keep: &dyn Fn(&FillMarkout) -> bool| {
+            let mut c = Curve::new(label, self.horizons.len());
+            for f in self.fills().filter(|f| keep(f)) {
+                c.add(f);
+            }
+            c.finish()
+        };
+        MarkoutReport {
+            symbol:       self.symbol.clone(),
+            horizons_ms:  self.cfg.horizons_ms.clone(),
+            all:          curve("all".into(), &|_| true),
+            by_side:      [Side::Buy, Side::Sell].into_iter()
+                .map(|s| curve(format!("{s:?}").to_lowercase(), &|f| f.side == s))
+                .collect(),
+            by_inventory: (0..=self.cfg.inv_buckets.len())
+                .map(|k| curve(self.bucket_label(k), &|f| self.bucket(f.inv) == k))
+                .collect(),
+        }
+    }
+
+    /// Log the curves and write the configured CSV / JSON files.
+    pub fn finish(&self) {
+        let lines = report_lines(&self.report());
+        for line in lines {
+            info!("{line}");
+        }
+        if let Some(path) = &self.cfg.csv {
+            if let Err(e) = self.write_csv(path) {
+                warn!("markouts not written to {path}: {e}");
+            }
+        }
+        if let Some(path) = &self.cfg.json {
+            if let Err(e) = self.write_json(path) {
+                warn!("markout curves not written to {path}: {e}");
+            }
+        }
+    }
+
+    /// One row per fill; a horizon past the end of the session is empty.
+    pub fn write_csv(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write!(out, "at_secs,id,side,px,qty,inv,edge")?;
+        for ms in &self.cfg.horizons_ms {
+            write!(out, ",markout_{ms}ms")?;
+        }
+        writeln!(out)?;
+        for f in self.fills() {
+            write!(out, "{:.6},{},{:?},{},{},{},{}", f.at, f.id, f.side, f.px, f.qty, f.inv, f.edge)?;
+            for m in &f.markouts {
+                match m {
+                    Some(m) => write!(out, ",{m}")?,
+                    None    => write!(out, ",")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+
+    pub fn write_json(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, &self.report())?;
+        out.write_all(b"\n")?;
+        out.flush()
+    }
+}
+
+/// Markout curves; `markouts[i]` is at `horizons_ms[i]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkoutReport {
+    pub symbol:       String,
+    pub horizons_ms:  Vec<u64>,
+    pub all:          Curve,
+    pub by_side:      Vec<Curve>,
+    pub by_inventory: Vec<Curve>,
+}
+
+/// Quantity-weighted edge and markouts of a set of fills.
+#[derive(Debug, Clone, Serialize)]
+pub struct Curve {
+    pub label:    String,
+    pub fills:    u64,
+    pub qty:      f64,
+    pub edge:     f64,
+    /// over the fills whose horizon passed before the session ended
+    pub markouts: Vec<f64>,
+    #[serde(skip)]
+    sums:         Vec<(f64, f64)>,
+}
+
+impl Curve {
+    fn new(label: String, horizons: usize) -> Self {
+        Self { label, fills: 0, qty: 0.0, edge: 0.0, markouts: Vec::new(), sums: vec![(0.0, 0.0); horizons] }
+    }
+
+    fn add(&mut self, f: &FillMarkout) {
+        self.fills += 1;
+        self.qty += f.qty;
+        self.edge += f.edge * f.qty;
+        for (sum, m) in self.sums.iter_mut().zip(&f.markouts) {
+            if let Some(m) = m {
+                sum.0 += m * f.qty;
+                sum.1 += f.qty;
+            }
+        }
+    }
+
+    /// Turn the sums into means.
+    fn finish(mut self) -> Self {
+        let mean = |sum: f64, qty: f64| if qty > 0.0 { sum / qty } else { 0.0 };
+        self.edge = mean(self.edge, self.qty);
+        self.markouts = self.sums.iter().map(|&(s, q)| mean(s, q)).collect();
+        self
+    }
+}
+
+/// The report as a table, one line per curve.
+fn report_lines(r: &MarkoutReport) -> Vec<String> {
+    let mut header = format!("MARKOUT {:<18} {:>6} {:>9}", r.symbol, "fills", "edge");
+    for ms in &r.horizons_ms {
+        header += &format!(" {:>9}", format!("{ms}ms"));
+    }
+    let row = |c: &Curve| {
+        let mut line = format!("MARKOUT {:<18} {:>6} {:>9.4}", c.label, c.fills, c.edge);
+        for m in &c.markouts {
+            line += &format!(" {m:>9.4}");
+        }
+        line
+    };
+    std::iter::once(header)
+        .chain(std::iter::once(&r.all).chain(&r.by_side).chain(&r.by_inventory).map(row))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hft_core::Liquidity;
+
+    fn fill(id: OrderId, side: Side, px: f64, qty: f64, ts: Instant) -> Fill {
+        Fill { id, symbol: "SIMPERP".into(), side, px, qty, leaves: 0.0, liquidity: Liquidity::Maker, fee: 0.0, ts }
+    }
+
+    fn close(a: f64, b: f64) -> bool { (a - b).abs() < 1e-9 }
+
+    #[test]
+    fn horizons_settle_on_the_first_mid_after_them() {
+        let cfg = MarkoutCfg { horizons_ms: vec![100, 1000], inv_buckets: vec![-5.0, 5.0], csv: None, json: None };
+        let mut m = Markouts::new(cfg, "SIMPERP");
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        m.on_fill(&fill(9, Side::Buy, 99.0, 1.0, at(0)), 0.0);   // no mid yet: ignored
+        m.on_mid(at(0), 100.0);
+        m.on_fill(&fill(1, Side::Buy, 99.9, 2.0, at(0)), 0.0);    // edge +0.1
+        m.on_mid(at(50), 100.2);
+        m.on_fill(&fill(2, Side::Sell, 100.3, 1.0, at(50)), 6.0); // edge +0.1
+        m.on_mid(at(100), 99.8);   // buy @100ms: −0.1
+        m.on_mid(at(149), 99.9);   // sell's 100 ms not reached yet
+        m.on_mid(at(150), 100.0);  // sell @100ms: +0.3
+        m.on_mid(at(1000), 100.5); // buy @1s: +0.6
+        m.on_mid(at(1100), 100.6); // sell @1s: −0.3
+        m.on_fill(&fill(3, Side::Sell, 100.1, 1.0, at(1100)), -6.0); // edge −0.5, never settles
+
+        let by_id = |id| m.fills().find(|f| f.id == id).unwrap().clone();
+        assert!(m.fills().all(|f| f.id != 9));
+        let (buy, sell, late) = (by_id(1), by_id(2), by_id(3));
+        assert!(close(buy.edge, 0.1) && close(sell.edge, 0.1) && close(late.edge, -0.5));
+        assert!(close(buy.markouts[0].unwrap(), -0.1) && close(buy.markouts[1].unwrap(), 0.6));
+        assert!(close(sell.markouts[0].unwrap(), 0.3) && close(sell.markouts[1].unwrap(), -0.3));
+        assert_eq!(late.markouts, [None, None]);
+        assert_eq!((m.done.len(), m.open.len()), (2, 1));
+
+        // inventory before the fill picks the bucket
+        assert_eq!([m.bucket(buy.inv), m.bucket(sell.inv), m.bucket(late.inv)], [1, 2, 0]);
+
+        let r = m.report();
+        assert_eq!((r.all.fills, r.all.qty), (3, 4.0));
+        assert!(close(r.all.edge, -0.05));
+        assert!(close(r.all.markouts[0], 0.1 / 3.0) && close(r.all.markouts[1], 0.3));
+
+        let [bought, sold] = &r.by_side[..] else { panic!("two sides") };
+        assert_eq!((bought.label.as_str(), bought.fills, sold.fills), ("buy", 1, 2));
+        assert!(close(sold.edge, -0.2));
+        assert!(close(sold.markouts[0], 0.3) && close(sold.markouts[1], -0.3));
+
+        let labels: Vec<(&str, u64)> = r.by_inventory.iter().map(|c| (c.label.as_str(), c.fills)).collect();
+        assert_eq!(labels, [("inv < -5", 1), ("-5 <= inv < 5", 1), ("inv >= 5", 1)]);
+        assert_eq!(r.by_inventory[0].markouts, [0.0, 0.0], "nothing settled in the short bucket");
+        assert!(close(r.by_inventory[1].markouts[1], 0.6));
+        assert!(close(r.by_inventory[2].markouts[0], 0.3));
+    }
+}
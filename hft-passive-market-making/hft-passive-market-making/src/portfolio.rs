@@ -0,0 +1,162 @@
+//! Several symbols quoted from one process: one [`InventoryMm`] book per
+//! symbol behind a single engine, sharing the venue link, the risk budget
+//! and the PnL summary.
+//!
+//! Book `k` of `n` numbers its orders `k+1, k+1+n, …`, so every execution
+//! report finds its way back to the book that sent the order.
+
+use crate::config::MmCfg;
+use crate::strategy::InventoryMm;
+use hft_core::{ExecReport, MarketData, OrderId, OrderIds, OrderReq, Strategy};
+use std::time::Instant;
+use tracing::info;
+
+pub struct Portfolio {
+    books: Vec<InventoryMm>,
+}
+
+impl Portfolio {
+    pub fn new(cfg: &MmCfg) -> Self {
+        let cfgs = cfg.books();
+        let n = cfgs.len() as OrderId;
+        let books = cfgs.into_iter().zip(1..)
+            .map(|(c, first)| InventoryMm::new(c).ids(OrderIds::strided(first, n)))
+            .collect();
+        Self { books }
+    }
+
+    pub fn books(&self) -> &[InventoryMm] { &self.books }
+
+    /// The book that sent order `id`.
+    pub fn book_of(&self, id: OrderId) -> &InventoryMm {
+        &self.books[self.slot(id)]
+    }
+
+    /// Σ |inventory| over all books.
+    pub fn gross_inv(&self) -> f64 {
+        self.books.iter().map(|b| b.inv().abs()).sum()
+    }
+
+    /// Summed PnL of every book and hedge; logged only when quoting more
+    /// than one symbol, the per-symbol `PNL` lines say it all otherwise.
+    pub fn log_summary(&self) {
+        if self.books.len() < 2 {
+            return;
+        }
+        let positions = self.books.iter()
+            .flat_map(|b| std::iter::once(b.position()).chain(b.hedger().map(|h| h.position())));
+        let (mut realized, mut unrealized, mut fees) = (0.0, 0.0, 0.0);
+        for p in positions {
+            realized += p.realized();
+            unrealized += p.unrealized();
+            fees += p.fees();
+        }
+        info!("PORTFOLIO symbols={} gross_inv={:.1} realized={:.2} unrealized={:.2} fees={:.2} net={:.2}",
+              self.books.len(), self.gross_inv(), realized, unrealized, fees,
+              realized + unrealized - fees);
+    }
+
+    fn slot(&self, id: OrderId) -> usize {
+        (id.saturating_sub(1) % self.books.len() as OrderId) as usize
+    }
+}
+
+impl Strategy for Portfolio {
+    /// Every book sees every event and acts on its own symbols only.
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        self.books.iter_mut().flat_map(|b| b.on_market_data(md)).collect()
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        let k = self.slot(er.id());
+        self.books[k].on_execution(er)
+    }
+
+    fn on_timer(&mut self, now: Instant) -> Vec<OrderReq> {
+        for b in &mut self.books {
+            b.on_timer(now);
+        }
+        self.log_summary();
+        Vec::new()
+    }
+
+    fn on_sent(&mut self, req: &OrderReq) {
+        let k = self.slot(req.id());
+        self.books[k].on_sent(req);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SymbolCfg;
+    use crate::risk::Risk;
+    use figment::providers::{Format, Toml};
+    use hft_core::{Fill, Liquidity, Order, Side, Tick};
+
+    /// `Config.toml` plus a second symbol, under a gross limit of 15.
+    fn cfg() -> MmCfg {
+        let cfg = MmCfg::extract(figment::Figment::from(Toml::string(include_str!("../Config.toml")))).unwrap();
+        let alt = SymbolCfg {
+            symbol: "SIMALT".into(), mid_px: Some(25.0), half_spread: None, tick_sz: None,
+            size: None, inv_limit: None, inv_spread_mult: None,
+        };
+        MmCfg { symbols: vec![alt], gross_inv_limit: Some(15.0), ..cfg }
+    }
+
+    fn tick(symbol: &str, mid: f64, ts: Instant) -> MarketData {
+        MarketData::Tick(Tick { symbol: symbol.into(), bid: mid - 0.01, ask: mid + 0.01, ts })
+    }
+
+    fn fill(id: OrderId, symbol: &str, qty: f64) -> ExecReport {
+        ExecReport::Fill(Fill {
+            id, symbol: symbol.into(), side: Side::Buy, px: 100.0, qty, leaves: 0.0,
+            liquidity: Liquidity::Maker, fee: 0.0, ts: Instant::now(),
+        })
+    }
+
+    #[test]
+    fn strided_ids_route_back_to_their_book() {
+        let (mut pf, t0) = (Portfolio::new(&cfg()), Instant::now());
+        let mut reqs = pf.on_market_data(&tick("SIMPERP", 100.0, t0));
+        reqs.extend(pf.on_market_data(&tick("SIMALT", 25.0, t0)));
+        let ids: Vec<(OrderId, String)> = reqs.iter()
+            .map(|r| match r {
+                OrderReq::New(o) => (o.id, o.symbol.to_string()),
+                other => panic!("expected a new order, got {other:?}"),
+            })
+            .collect();
+        let want = [(1, "SIMPERP"), (3, "SIMPERP"), (2, "SIMALT"), (4, "SIMALT")];
+        assert_eq!(ids, want.map(|(id, s)| (id, s.to_string())));
+        for (id, symbol) in &ids {
+            assert_eq!(&pf.book_of(*id).cfg().symbol, symbol);
+        }
+
+        for r in &reqs {
+            pf.on_sent(r);
+        }
+        pf.on_execution(&fill(4, "SIMALT", 3.0));
+        let inv: Vec<f64> = pf.books().iter().map(|b| b.inv()).collect();
+        assert_eq!(inv, [0.0, 3.0]);
+    }
+
+    #[test]
+    fn books_share_the_gross_limit() {
+        let (mut pf, t0) = (Portfolio::new(&cfg()), Instant::now());
+        pf.on_execution(&fill(1, "SIMPERP", 8.0));
+        pf.on_execution(&fill(2, "SIMALT", 6.0));
+        assert_eq!(pf.gross_inv(), 14.0);
+
+        let mut risk = Risk::new(cfg());
+        let order = |id, symbol: &str, side, qty| OrderReq::New(Order::limit(id, symbol, side, 25.0, qty));
+        // each book is inside its own limit of 10, but together they would pass 15
+        assert!(!risk.allow(&pf, &order(6, "SIMALT", Side::Buy, 2.0), t0));
+        assert!(!risk.allow(&pf, &order(5, "SIMPERP", Side::Buy, 2.0), t0));
+        assert!(risk.allow(&pf, &order(7, "SIMPERP", Side::Buy, 1.0), t0));
+        // reducing either book is always fine
+        assert!(risk.allow(&pf, &order(8, "SIMALT", Side::Sell, 2.0), t0));
+
+        let mut unbounded = Risk::new(MmCfg { gross_inv_limit: None, ..cfg() });
+        assert!(unbounded.allow(&pf, &order(6, "SIMALT", Side::Buy, 2.0), t0));
+    }
+}
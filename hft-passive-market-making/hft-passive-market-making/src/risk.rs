@@ -1,27 +1,41 @@
-use crate::{models::*, config::MmCfg};
+use crate::config::MmCfg;
+use crate::portfolio::Portfolio;
+use hft_core::OrderReq;
 
 pub struct Risk {
     cfg: MmCfg,
-    max_orders_sec: usize,
     sent_last_sec: usize,
-    last_ts: std::time::Instant,
+    last_ts: Option<std::time::Instant>,
 }
 
 impl Risk {
     pub fn new(cfg: MmCfg) -> Self {
-        Self { cfg, max_orders_sec: 20, sent_last_sec: 0,
-               last_ts: std::time::Instant::now() }
+        Self { cfg, sent_last_sec: 0, last_ts: None }
     }
 
-    pub fn allow(&mut self, _ord: &Order, inv_after: f64) -> bool {
-        if inv_after.abs() > self.cfg.inv_limit { return false; }
+    /// Every message counts against the rate limit, shared by all symbols;
+    /// only requests that grow their symbol's inventory are checked against
+    /// its limit and the gross limit, so a book over its limit can still
+    /// work back down.
+    /// `now` is the market-data timestamp, so the rate window follows the
+    /// session clock.
+    pub fn allow(&mut self, pf: &Portfolio, req: &OrderReq, now: std::time::Instant) -> bool {
+        let adds_risk = !matches!(req, OrderReq::Cancel { .. });
+        if adds_risk {
+            let book = pf.book_of(req.id());
+            let inv_after = book.inv_after(req);
+            let grows = inv_after.abs() > book.inv().abs();
+            if grows && inv_after.abs() > book.cfg().inv_limit { return false; }
+            let gross_after = pf.gross_inv() - book.inv().abs() + inv_after.abs();
+            if grows && self.cfg.gross_inv_limit.is_some_and(|l| gross_after > l) { return false; }
+        }
 
-        let now = std::time::Instant::now();
-        if now.duration_since(self.last_ts).as_secs() >= 1 {
+        let last = *self.last_ts.get_or_insert(now);
+        if now.duration_since(last).as_secs() >= 1 {
             self.sent_last_sec = 0;
-            self.last_ts = now;
+            self.last_ts = Some(now);
         }
-        if self.sent_last_sec >= self.max_orders_sec { return false; }
+        if self.sent_last_sec >= self.cfg.max_orders_sec { return false; }
         self.sent_last_sec += 1;
         true
     }
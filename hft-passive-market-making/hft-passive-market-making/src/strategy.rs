@@ -1,29 +1,539 @@
-use crate::{config::MmCfg, models::*};
+use crate::avellaneda::AvellanedaStoikov;
+use crate::config::{MmCfg, QuoteModel};
+use crate::estimators::Estimators;
+use crate::hedge::Hedger;
+use crate::ladder::{self, Level};
+use crate::liquidation::Liquidator;
+use crate::markout::Markouts;
+use hft_core::*;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// One of our working quotes as the strategy believes it to be.
+#[derive(Debug, Clone)]
+struct Quote {
+    id:  OrderId,
+    px:  f64,
+    qty: f64,
+    /// a New / Replace is in flight — wait for the venue before amending
+    pending: bool,
+    /// session time the current price / size was sent
+    sent_at: Instant,
+}
+
+/// Order-message counts of the quoting loop.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuoteStats {
+    pub news:     u64,
+    pub replaces: u64,
+    pub cancels:  u64,
+    /// amends held back by the refresh thresholds
+    pub saved:    u64,
+}
 
 pub struct InventoryMm {
     cfg: MmCfg,
-    inv: f64,
-    pnl: f64,
+    /// inventory, average cost and PnL, marked to the latest mid
+    pos: Position,
+    ids: OrderIds,
+    /// price levels quoted on each side, top first
+    ladder: Vec<Level>,
+    /// working quote per side and level: [bids, asks]
+    quotes: [Vec<Option<Quote>>; 2],
+    /// ladder slot of each New generated by the last `quote()`
+    planned: Vec<(OrderId, usize)>,
+    /// session time of the latest tick
+    now: Option<Instant>,
+    stats: QuoteStats,
+    /// set when quoting with `model = "avellaneda_stoikov"`
+    avst: Option<AvellanedaStoikov>,
+    /// volatility and trade arrivals measured on our symbol
+    est: Estimators,
+    /// post-fill markouts, when `[markouts]` is configured
+    markouts: Option<Markouts>,
+    /// hedges inventory on a second symbol, when `[hedge]` is configured
+    hedger: Option<Hedger>,
+    /// winds the book down at the end of the session, when `[liquidation]`
+    /// is configured
+    liquidator: Option<Liquidator>,
 }
 
 impl InventoryMm {
     pub fn new(cfg: MmCfg) -> Self {
-        Self { cfg, inv: 0.0, pnl: 0.0 }
+        let avst = match cfg.model {
+            QuoteModel::Linear => None,
+            QuoteModel::AvellanedaStoikov => cfg.avellaneda_stoikov.clone().map(AvellanedaStoikov::new),
+        };
+        let est = Estimators::new(&cfg.estimators);
+        let markouts = cfg.markouts.clone().map(|m| Markouts::new(m, &cfg.symbol));
+        let hedger = cfg.hedge.clone().map(Hedger::new);
+        let liquidator = cfg.liquidation.clone().map(Liquidator::new);
+        let ladder = ladder::levels(&cfg);
+        let quotes = [vec![None; ladder.len()], vec![None; ladder.len()]];
+        Self {
+            cfg,
+            pos: Position::default(),
+            ids: OrderIds::default(),
+            ladder,
+            quotes,
+            planned: Vec::new(),
+            now: None,
+            stats: QuoteStats::default(),
+            avst,
+            est,
+            markouts,
+            hedger,
+            liquidator,
+        }
+    }
+
+    /// Draw order ids from `ids` rather than 1, 2, 3, …, so several books
+    /// can share a venue.
+    pub fn ids(mut self, ids: OrderIds) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Target top-of-ladder (bid, ask) for the current market, never
+    /// crossing the touch. Inventory skews the quotes only as far as it is
+    /// not hedged.
+    pub fn target(&mut self, tick: &Tick) -> (f64, f64) {
+        let mid = tick.mid();
+        let inv = self.exposure();
+        let (bid, ask) = match &mut self.avst {
+            Some(avst) => {
+                let sigma2 = self.est.vol.var().unwrap_or(0.0);
+                avst.quotes(mid, inv, sigma2, tick.ts)
+            }
+            None => {
+                let skew = (inv / self.cfg.inv_limit).clamp(-1.0, 1.0);
+                let half = self.cfg.half_spread * self.spread_mult()
+                    * (1.0 + skew.abs() * self.cfg.inv_spread_mult);
+                (mid - half, mid + half)
+            }
+        };
+        (bid.min(tick.ask - self.cfg.tick_sz), ask.max(tick.bid + self.cfg.tick_sz))
+    }
+
+    /// Regime multiplier on `half_spread`; 1 until the estimators have
+    /// warmed up or when `adaptive_spread` is off.
+    pub fn spread_mult(&self) -> f64 {
+        let Some(a) = &self.cfg.adaptive_spread else { return 1.0 };
+        match (self.est.vol.sigma(), self.est.arrivals.rate()) {
+            (Some(sigma), Some(rate)) => {
+                ((sigma / a.vol_ref) * (rate / a.arrival_ref)).sqrt().clamp(a.min_mult, a.max_mult)
+            }
+            _ => 1.0,
+        }
+    }
+
+    pub fn estimators(&self) -> &Estimators { &self.est }
+    pub fn markouts(&self) -> Option<&Markouts> { self.markouts.as_ref() }
+
+    /// Requests that move every ladder level onto the target: a fresh order
+    /// where we have none, an amend where the price moved enough (see
+    /// [`RefreshCfg`](crate::config::RefreshCfg)), a cancel where filling
+    /// this level and those in front of it would breach the inventory limit
+    /// and leave more inventory than we hold now.
+    pub fn quote(&mut self, tick: &Tick) -> Vec<OrderReq> {
+        let (bid, ask) = self.target(tick);
+        let mut reqs = Vec::new();
+        self.planned.clear();
+        self.now = Some(tick.ts);
+        let pos = self.pos.qty();
+
+        for (top, side) in [(bid, Side::Buy), (ask, Side::Sell)] {
+            let mut cum = 0.0;
+            for (k, level) in self.ladder.iter().enumerate() {
+                // deeper levels sit further from the mid
+                let px = top - side.sign() * level.offset;
+                let qty = level.qty;
+                cum += qty;
+                // past the limit, levels that work inventory down still quote
+                let room = (pos + side.sign() * cum).abs() <= pos.abs().max(self.cfg.inv_limit);
+
+                match &self.quotes[slot(side)][k] {
+                    Some(q) if q.pending => {}
+                    Some(q) if !room => reqs.push(OrderReq::Cancel { id: q.id }),
+                    Some(q) if q.qty != qty || self.stale(q, px, tick.ts) => {
+                        reqs.push(OrderReq::Replace { id: q.id, px, qty });
+                    }
+                    Some(q) if (q.px - px).abs() > 1e-9 => self.stats.saved += 1,
+                    Some(_) => {}
+                    None if room => {
+                        let id = self.ids.next();
+                        self.planned.push((id, k));
+                        reqs.push(OrderReq::New(Order::limit(id, &self.cfg.symbol, side, px, qty)));
+                    }
+                    None => {}
+                }
+            }
+        }
+        reqs
+    }
+
+    /// Once quoting has stopped, cancels for every working quote and the
+    /// liquidator's child orders; `None` while still quoting.
+    fn liquidate(&mut self, tick: &Tick) -> Option<Vec<OrderReq>> {
+        let liq = self.liquidator.as_mut()?;
+        let children = liq.on_tick(tick, self.pos.qty(), self.cfg.tick_sz, &mut self.ids)?;
+        self.now = Some(tick.ts);
+        let mut reqs: Vec<OrderReq> = self.quotes.iter().flatten().flatten()
+            .filter(|q| !q.pending)
+            .map(|q| OrderReq::Cancel { id: q.id })
+            .collect();
+        reqs.extend(children);
+        Some(reqs)
+    }
+
+    /// A hedge order if inventory has drifted past the hedge threshold, or
+    /// one closing the hedge once the liquidation deadline has passed.
+    fn hedge(&mut self) -> Option<OrderReq> {
+        let hedger = self.hedger.as_ref()?;
+        let flatten = self.liquidator.as_ref().zip(self.now).is_some_and(|(l, now)| l.past_deadline(now));
+        if flatten {
+            hedger.flatten(&mut self.ids).map(OrderReq::New)
+        } else {
+            hedger.order(self.pos.qty(), &mut self.ids).map(OrderReq::New)
+        }
     }
 
-    pub fn quote(&self, tick: &Tick) -> (f64, f64) {
-        let mid = (tick.bid + tick.ask) / 2.0;
-        let skew = (self.inv / self.cfg.inv_limit).clamp(-1.0, 1.0);
-        let half = self.cfg.half_spread * (1.0 + skew.abs() * self.cfg.inv_spread_mult);
-        (mid - half, mid + half)
+    fn is_liquidation(&self, id: OrderId) -> bool {
+        self.liquidator.as_ref().is_some_and(|l| l.owns(id))
     }
 
-    pub fn on_fill(&mut self, f: &Fill) {
-        let dir = if matches!(f.side, Side::Buy) { 1.0 } else { -1.0 };
-        self.inv += dir * f.qty;
-        self.pnl -= dir * f.qty * f.px;
+    fn is_hedge(&self, symbol: &str) -> bool {
+        self.hedger.as_ref().is_some_and(|h| h.symbol() == symbol)
     }
 
-    pub fn inv(&self) -> f64 { self.inv }
-    pub fn pnl(&self) -> f64 { self.pnl }
+    /// Whether a quote resting at `q.px` should move to `px`.
+    fn stale(&self, q: &Quote, px: f64, now: Instant) -> bool {
+        let moved = (q.px - px).abs();
+        let Some(r) = &self.cfg.refresh else { return moved > 1e-9 };
+        let aged = r.max_age_ms
+            .is_some_and(|ms| now.saturating_duration_since(q.sent_at) >= Duration::from_millis(ms));
+        moved >= r.min_move_ticks * self.cfg.tick_sz - 1e-9 || (aged && moved > 1e-9)
+    }
+
+    /// Record a request that actually went out to the venue.
+    fn record_sent(&mut self, req: &OrderReq) {
+        if let (Some(h), OrderReq::New(o)) = (&mut self.hedger, req) {
+            if o.symbol == h.symbol() {
+                h.on_sent(o);
+                return;
+            }
+        }
+        if self.is_liquidation(req.id()) {
+            return;
+        }
+        let now = self.now.unwrap_or_else(Instant::now);
+        match req {
+            OrderReq::New(o) => {
+                self.stats.news += 1;
+                let Some(&(_, k)) = self.planned.iter().find(|(id, _)| *id == o.id) else { return };
+                self.quotes[slot(o.side)][k] = o.px.map(|px| Quote {
+                    id: o.id, px, qty: o.qty, pending: true, sent_at: now,
+                });
+            }
+            OrderReq::Replace { id, px, qty } => {
+                self.stats.replaces += 1;
+                if let Some(q) = self.quote_mut(*id) {
+                    q.px = *px;
+                    q.qty = *qty;
+                    q.pending = true;
+                    q.sent_at = now;
+                }
+            }
+            OrderReq::Cancel { id } => {
+                self.stats.cancels += 1;
+                if let Some(q) = self.quote_mut(*id) {
+                    q.pending = true;
+                }
+            }
+        }
+    }
+
+    fn on_exec(&mut self, er: &ExecReport) {
+        if let Some(h) = &mut self.hedger {
+            match er {
+                ExecReport::Fill(f) if f.symbol == h.symbol() => return h.on_fill(f),
+                ExecReport::Reject { id, .. } if h.owns(*id) => return h.done(*id),
+                _ => {}
+            }
+        }
+        if let Some(l) = &mut self.liquidator {
+            match er {
+                ExecReport::Fill(f) if l.owns(f.id) => {
+                    l.on_fill(f);
+                    return self.pos.on_fill(f.side, f.qty, f.px, f.fee);
+                }
+                ExecReport::NewOrderAck { id } | ExecReport::CancelAck { id } | ExecReport::Reject { id, .. }
+                    if l.owns(*id) => return,
+                _ => {}
+            }
+        }
+        match er {
+            ExecReport::NewOrderAck { id } => {
+                if let Some(q) = self.quote_mut(*id) {
+                    q.pending = false;
+                }
+            }
+            ExecReport::ReplaceAck { id, px, qty } => {
+                if let Some(q) = self.quote_mut(*id) {
+                    q.px = *px;
+                    q.qty = *qty;
+                    q.pending = false;
+                }
+            }
+            ExecReport::Reject { id, .. } | ExecReport::CancelAck { id } => self.forget(*id),
+            ExecReport::Fill(f) => {
+                self.on_fill(f);
+                if f.leaves <= 0.0 {
+                    self.forget(f.id);
+                } else if let Some(q) = self.quote_mut(f.id) {
+                    // partially hit: next quote() tops it back up to size
+                    q.qty = f.leaves;
+                }
+            }
+        }
+    }
+
+    fn on_fill(&mut self, f: &Fill) {
+        if let Some(m) = &mut self.markouts {
+            m.on_fill(f, self.pos.qty());
+        }
+        self.pos.on_fill(f.side, f.qty, f.px, f.fee);
+    }
+
+    fn quote_mut(&mut self, id: OrderId) -> Option<&mut Quote> {
+        self.quotes.iter_mut().flatten().flatten().find(|q| q.id == id)
+    }
+
+    fn forget(&mut self, id: OrderId) {
+        for q in self.quotes.iter_mut().flatten() {
+            if q.as_ref().is_some_and(|q| q.id == id) {
+                *q = None;
+            }
+        }
+    }
+
+    /// Inventory if `req` were to fill completely (risk-check helper).
+    pub fn inv_after(&self, req: &OrderReq) -> f64 {
+        match req {
+            OrderReq::New(o) if self.is_hedge(&o.symbol) => self.pos.qty(),
+            OrderReq::New(o) => self.pos.qty() + o.side.sign() * o.qty,
+            OrderReq::Replace { id, qty, .. } => {
+                let side = if self.quotes[0].iter().flatten().any(|q| q.id == *id) {
+                    Side::Buy
+                } else {
+                    Side::Sell
+                };
+                self.pos.qty() + side.sign() * qty
+            }
+            OrderReq::Cancel { .. } => self.pos.qty(),
+        }
+    }
+
+    pub fn cfg(&self) -> &MmCfg { &self.cfg }
+    pub fn inv(&self) -> f64 { self.pos.qty() }
+
+    /// Inventory net of the hedge (the inventory itself without a hedger).
+    pub fn exposure(&self) -> f64 {
+        self.hedger.as_ref().map_or(self.pos.qty(), |h| h.exposure(self.pos.qty()))
+    }
+    pub fn quote_stats(&self) -> QuoteStats { self.stats }
+    pub fn position(&self) -> &Position { &self.pos }
+    pub fn hedger(&self) -> Option<&Hedger> { self.hedger.as_ref() }
+
+    /// Residual inventory and cost of the end-of-session liquidation.
+    pub fn log_liquidation(&self) {
+        if let Some(l) = &self.liquidator {
+            l.log_report(&self.cfg.symbol, self.pos.qty());
+        }
+    }
+
+    /// One-line PnL summary, logged periodically and at the end of a run.
+    pub fn log_summary(&self) {
+        let p = &self.pos;
+        info!("PNL {} inv={:.1} avg={:.2} mark={:.2} realized={:.2} unrealized={:.2} fees={:.2} net={:.2}",
+              self.cfg.symbol, p.qty(), p.avg_px(), p.last_mark().unwrap_or_default(),
+              p.realized(), p.unrealized(), p.fees(), p.net());
+        if let Some(h) = &self.hedger {
+            let hp = h.position();
+            info!("HEDGE {} pos={:.1} avg={:.2} mark={:.2} realized={:.2} unrealized={:.2} fees={:.2} net={:.2} exposure={:.1} total={:.2}",
+                  h.symbol(), hp.qty(), hp.avg_px(), hp.last_mark().unwrap_or_default(),
+                  hp.realized(), hp.unrealized(), hp.fees(), hp.net(),
+                  h.exposure(p.qty()), p.net() + hp.net());
+        }
+    }
+
+    /// Order messages sent and amends saved by the refresh thresholds.
+    pub fn log_quoting(&self) {
+        let s = &self.stats;
+        let sent = s.news + s.replaces + s.cancels;
+        info!("QUOTES {} sent={} (new={} replace={} cancel={}) saved={} ({:.0}%)",
+              self.cfg.symbol, sent, s.news, s.replaces, s.cancels, s.saved,
+              100.0 * s.saved as f64 / (sent + s.saved).max(1) as f64);
+    }
+
+    /// One-line view of the market estimators.
+    pub fn log_market(&self) {
+        info!("MKT {} sigma={:.4} arrivals={:.2}/s spread_mult={:.2}",
+              self.cfg.symbol,
+              self.est.vol.sigma().unwrap_or_default(),
+              self.est.arrivals.rate().unwrap_or_default(),
+              self.spread_mult());
+    }
+}
+
+impl Strategy for InventoryMm {
+    /// Feeds the estimators, then marks, settles markouts, requotes (or
+    /// liquidates, past the session stop) and hedges on every top-of-book
+    /// update; depth snapshots are ignored.
+    /// Hedge-instrument ticks only mark the hedge.
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        if md.symbol() != self.cfg.symbol {
+            if let (Some(h), MarketData::Tick(tick)) = (&mut self.hedger, md) {
+                h.on_tick(tick);
+            }
+            return Vec::new();
+        }
+        self.est.on_market_data(md);
+        match md {
+            MarketData::Tick(tick) => {
+                self.pos.mark(tick.mid());
+                if let Some(m) = &mut self.markouts {
+                    m.on_mid(tick.ts, tick.mid());
+                }
+                let mut reqs = match self.liquidate(tick) {
+                    Some(reqs) => reqs,
+                    None => self.quote(tick),
+                };
+                reqs.extend(self.hedge());
+                reqs
+            }
+            MarketData::Book(_) | MarketData::Trade(_) => Vec::new(),
+        }
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        self.on_exec(er);
+        match er {
+            ExecReport::Fill(fill) if self.is_hedge(&fill.symbol) => {
+                info!("HEDGE FILL #{} {:?} {} qty={:.2} px={:.2} fee={:.4}",
+                      fill.id, fill.side, fill.symbol, fill.qty, fill.px, fill.fee);
+            }
+            ExecReport::Fill(fill) => {
+                let p = &self.pos;
+                info!("FILL #{} {:?} {:?} qty={:.2} px={:.2} fee={:.4} leaves={:.2} inv={:.1} avg={:.2} rpnl={:.2} upnl={:.2} fees={:.2}",
+                      fill.id, fill.side, fill.liquidity, fill.qty, fill.px, fill.fee, fill.leaves,
+                      p.qty(), p.avg_px(), p.realized(), p.unrealized(), p.fees());
+            }
+            // the cancel chasing a fully filled IOC child is expected to bounce
+            ExecReport::Reject { id, .. } if self.is_liquidation(*id) => {}
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            other => debug!("{other:?}"),
+        }
+        Vec::new()
+    }
+
+    fn on_timer(&mut self, _now: std::time::Instant) -> Vec<OrderReq> {
+        self.log_summary();
+        self.log_market();
+        self.log_quoting();
+        Vec::new()
+    }
+
+    fn on_sent(&mut self, req: &OrderReq) {
+        self.record_sent(req);
+    }
+}
+
+fn slot(side: Side) -> usize {
+    match side { Side::Buy => 0, Side::Sell => 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LadderCfg;
+    use figment::providers::{Format, Toml};
+
+    /// The crate's `Config.toml`: linear model, half spread 0.25, tick 0.01,
+    /// refresh after 2 ticks or 500 ms, inventory limit 10.
+    fn mm(ladder: Option<LadderCfg>) -> InventoryMm {
+        let cfg = MmCfg::extract(figment::Figment::from(Toml::string(include_str!("../Config.toml")))).unwrap();
+        InventoryMm::new(MmCfg { ladder, ..cfg })
+    }
+
+    fn tick(t0: Instant, ms: u64, mid: f64) -> Tick {
+        Tick { symbol: "SIMPERP".into(), bid: mid - 0.01, ask: mid + 0.01, ts: t0 + Duration::from_millis(ms) }
+    }
+
+    /// Send `reqs` and have the venue acknowledge them.
+    fn work(mm: &mut InventoryMm, reqs: &[OrderReq]) {
+        for req in reqs {
+            mm.on_sent(req);
+            let ack = match *req {
+                OrderReq::New(ref o)               => ExecReport::NewOrderAck { id: o.id },
+                OrderReq::Replace { id, px, qty } => ExecReport::ReplaceAck { id, px, qty },
+                OrderReq::Cancel { id }           => ExecReport::CancelAck { id },
+            };
+            mm.on_execution(&ack);
+        }
+    }
+
+    fn replaced_px(reqs: &[OrderReq]) -> Vec<f64> {
+        reqs.iter()
+            .map(|r| match r {
+                OrderReq::Replace { px, .. } => (px * 100.0).round() / 100.0,
+                other => panic!("expected a replace, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn small_moves_wait_and_old_quotes_follow() {
+        let (mut mm, t0) = (mm(None), Instant::now());
+        let reqs = mm.quote(&tick(t0, 0, 100.0));
+        assert!(matches!(&reqs[..], [OrderReq::New(b), OrderReq::New(a)]
+                         if b.px == Some(99.75) && a.px == Some(100.25)));
+        work(&mut mm, &reqs);
+
+        // one tick of movement is below min_move_ticks
+        assert!(mm.quote(&tick(t0, 100, 100.01)).is_empty());
+        assert_eq!(mm.quote_stats().saved, 2);
+
+        // two ticks is enough
+        let reqs = mm.quote(&tick(t0, 200, 100.02));
+        assert_eq!(replaced_px(&reqs), [99.77, 100.27]);
+        work(&mut mm, &reqs);
+
+        // a small move on a young quote waits, on a 500 ms old one it goes
+        assert!(mm.quote(&tick(t0, 300, 100.03)).is_empty());
+        let reqs = mm.quote(&tick(t0, 700, 100.03));
+        assert_eq!(replaced_px(&reqs), [99.78, 100.28]);
+        work(&mut mm, &reqs);
+
+        // an old quote still on target stays put
+        assert!(mm.quote(&tick(t0, 1_500, 100.03)).is_empty());
+        assert_eq!(mm.quote_stats().replaces, 4);
+    }
+
+    #[test]
+    fn past_the_limit_only_reducing_levels_quote() {
+        let ladder = LadderCfg { levels: 3, spacing: Default::default(), step: 0.05, ratio: 0.0, sizes: vec![5.0] };
+        let mut mm = mm(Some(ladder));
+        mm.pos.on_fill(Side::Buy, 12.0, 100.0, 0.0);
+
+        let reqs = mm.quote(&tick(Instant::now(), 0, 100.0));
+        let sides: Vec<(Side, f64)> = reqs.iter()
+            .map(|r| match r {
+                OrderReq::New(o) => (o.side, o.qty),
+                other => panic!("expected a new order, got {other:?}"),
+            })
+            .collect();
+        // every ask takes inventory towards flat (12 → 7 → 2 → −3); every bid adds
+        assert_eq!(sides, [(Side::Sell, 5.0); 3]);
+    }
 }
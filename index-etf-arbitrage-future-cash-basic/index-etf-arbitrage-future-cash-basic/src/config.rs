@@ -1,6 +1,7 @@
 //! Load strategy parameters.
 
 use anyhow::Result;
+use hft_core::FeeSchedule;
 use figment::providers::{Env, Format, Toml};
 use serde::Deserialize;
 use std::collections::BTreeMap;
@@ -16,13 +17,36 @@ pub struct ArbCfg {
     pub size_etf: f64,
     pub pos_limit: f64,
     pub tick_ms: u64,
+    /// Venue fee schedule: maker / taker bps and per-share fees, optionally
+    /// tiered by traded notional. Charged on every fill.
+    #[serde(default)]
+    pub fees: FeeSchedule,
+    /// Simulator RNG seed; the same seed replays the same session.
+    pub seed: u64,
+    /// Run on virtual time as fast as the CPU allows instead of real time.
+    #[serde(default)]
+    pub backtest: bool,
+    /// End the session after this many ticks (runs forever if unset).
+    pub max_ticks: Option<u64>,
+    /// Write every market-data event and execution to this JSONL file.
+    pub record: Option<String>,
+    /// Replay this recording instead of running the simulator.
+    pub replay: Option<String>,
+    /// Run on historical CSV/Parquet data (ETF + basket) instead of the simulator.
+    pub data: Option<String>,
+    /// Sharpe/Sortino bucket of the end-of-run report, in ms of session time.
+    pub report_bucket_ms: u64,
+    /// Also write the end-of-run report to this JSON file.
+    pub report_json: Option<String>,
 }
 
 impl ArbCfg {
     pub fn load() -> Result<Self> {
-        Ok(figment::Figment::from(Toml::file("Config.toml"))
+        let cfg: Self = figment::Figment::from(Toml::file("Config.toml"))
             .merge(Env::prefixed("IDX_"))
-            .extract()?)
+            .extract()?;
+        anyhow::ensure!(cfg.report_bucket_ms > 0, "report_bucket_ms must be positive");
+        Ok(cfg)
     }
 
     /// Convenience helpers
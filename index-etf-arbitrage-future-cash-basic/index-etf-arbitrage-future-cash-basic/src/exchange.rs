@@ -1,29 +1,51 @@
-use crate::models::*;
-use rand::Rng;
+use hft_core::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::BTreeMap;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tracing::info;
 
 /// Two-sided book simulator
 pub struct Exchange {
-    md_tx:   mpsc::Sender<Tick>,
-    fill_tx: mpsc::Sender<Fill>,
+    md_tx:   mpsc::Sender<MarketData>,
+    er_tx:   mpsc::Sender<ExecReport>,
     stocks:  BTreeMap<String, f64>, // live stock prices
     etf_px:  f64,                   // live ETF price
     weights: BTreeMap<String, f64>,
+    rng:     StdRng,                // sole source of randomness
+    now:     Instant,               // session time of the current step
+    fees:    FeeMeter,              // charges every fill
 }
 
 impl Exchange {
     pub fn new(
-        md_tx: mpsc::Sender<Tick>,
-        fill_tx: mpsc::Sender<Fill>,
+        md_tx: mpsc::Sender<MarketData>,
+        er_tx: mpsc::Sender<ExecReport>,
         weights: &BTreeMap<String, f64>,
         start_px: f64,
+        seed: u64,
     ) -> Self {
         let stocks = weights
             .keys()
             .map(|s| (s.clone(), start_px))
             .collect::<BTreeMap<_, _>>();
-        Self { md_tx, fill_tx, stocks, etf_px: start_px, weights: weights.clone() }
+        Self {
+            md_tx,
+            er_tx,
+            stocks,
+            etf_px: start_px,
+            weights: weights.clone(),
+            rng: StdRng::seed_from_u64(seed),
+            now: Instant::now(),
+            fees: FeeMeter::default(),
+        }
+    }
+
+    /// Charge fills with `fees` (none by default).
+    pub fn fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = FeeMeter::new(fees);
+        self
     }
 
     fn fair_value(&self) -> f64 {
@@ -33,44 +55,99 @@ impl Exchange {
             .sum::<f64>()
     }
 
-    pub async fn run(mut self, tick_ms: u64, mut od_rx: mpsc::Receiver<Order>) {
-        let mut intv =
-            tokio::time::interval(std::time::Duration::from_millis(tick_ms));
+    pub async fn run(mut self, mut clock: Clock, mut od_rx: mpsc::Receiver<OrderReq>,
+                     mut done_rx: mpsc::Receiver<()>) {
         loop {
             tokio::select! {
-                _ = intv.tick() => {
+                // requests already queued are handled before the next tick
+                biased;
+
+                Some(req) = od_rx.recv() => {
+                    for er in self.on_request(req) {
+                        let _ = self.er_tx.send(er).await;
+                    }
+                }
+
+                now = clock.tick() => {
+                    // ticks carry session time, not wall time
+                    let Some(now) = now else { break };
+                    self.now = now;
+
                     // ---- random walk each stock ---
                     for px in self.stocks.values_mut() {
-                        let noise = (rand::random::<f64>() - 0.5) * 0.002;
+                        let noise = (self.rng.gen::<f64>() - 0.5) * 0.002;
                         *px *= 1.0 + noise;
                     }
 
                     // ---- etf deviates from fair value by ±10 bps noise ----
                     let fv = self.fair_value();
-                    let basis_noise = (rand::random::<f64>() - 0.5) * 0.001;
+                    let basis_noise = (self.rng.gen::<f64>() - 0.5) * 0.001;
                     self.etf_px = fv * (1.0 + basis_noise);
 
-                    let now = std::time::Instant::now();
                     // publish ticks
                     for (sym, px) in &self.stocks {
-                        let _ = self.md_tx.send(Tick{symbol:sym.clone(),px:*px,ts:now}).await;
+                        let _ = self.md_tx.send(MarketData::Tick(Tick::at(sym, *px, now))).await;
                     }
-                    let _ = self.md_tx.send(Tick{symbol:"SIMETF".to_string(),px:self.etf_px,ts:now}).await;
-                }
+                    let _ = self.md_tx.send(MarketData::Tick(Tick::at("SIMETF", self.etf_px, now))).await;
 
-                Some(ord) = od_rx.recv() => {
-                    // Fill at mid immediately
-                    let px = if ord.symbol == "SIMETF" {
-                        self.etf_px
-                    } else {
-                        self.stocks[&ord.symbol]
-                    };
-                    let fill = Fill{ symbol: ord.symbol.clone(), px, qty: ord.qty, side: ord.side };
-                    let _ = self.fill_tx.send(fill).await;
+                    // wait for the strategy to react before stepping again
+                    if !self.settle(self.stocks.len() + 1, &mut od_rx, &mut done_rx).await {
+                        break;
+                    }
                 }
 
                 else => break,
             }
         }
+        info!("session over after {} ticks", clock.ticks());
+    }
+
+    /// Lockstep with the strategy: wait until it has reacted to the `n`
+    /// ticks just published, then take in everything it sent before
+    /// stepping again. This keeps a seeded run identical however fast
+    /// either side is.
+    async fn settle(&mut self,
+                    n: usize,
+                    od_rx: &mut mpsc::Receiver<OrderReq>,
+                    done_rx: &mut mpsc::Receiver<()>) -> bool
+    {
+        for _ in 0..n {
+            if done_rx.recv().await.is_none() {
+                return false;
+            }
+        }
+        while let Ok(req) = od_rx.try_recv() {
+            for er in self.on_request(req) {
+                let _ = self.er_tx.send(er).await;
+            }
+        }
+        true
+    }
+
+    /// Orders fill at mid immediately, so there is never anything left
+    /// resting to cancel or amend.
+    fn on_request(&mut self, req: OrderReq) -> Vec<ExecReport> {
+        match req {
+            OrderReq::New(ord) => {
+                let px = if ord.symbol == "SIMETF" {
+                    self.etf_px
+                } else if let Some(px) = self.stocks.get(&ord.symbol) {
+                    *px
+                } else {
+                    return vec![ExecReport::Reject { id: ord.id, reason: "unknown symbol".into() }];
+                };
+                vec![
+                    ExecReport::NewOrderAck { id: ord.id },
+                    ExecReport::Fill(self.fees.charge(Fill {
+                        id: ord.id, symbol: ord.symbol, px,
+                        qty: ord.qty, side: ord.side, leaves: 0.0,
+                        liquidity: Liquidity::Taker, fee: 0.0, ts: self.now,
+                    })),
+                ]
+            }
+            OrderReq::Cancel { id } | OrderReq::Replace { id, .. } => {
+                vec![ExecReport::Reject { id, reason: "unknown or closed order".into() }]
+            }
+        }
     }
 }
@@ -0,0 +1,6 @@
+//! ETF-vs-basket basis arbitrage and its index venue.
+
+pub mod config;
+pub mod exchange;
+pub mod risk;
+pub mod strategy;
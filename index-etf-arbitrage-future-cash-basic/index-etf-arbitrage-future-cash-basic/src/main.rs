@@ -1,15 +1,8 @@
-mod config;
-mod models;
-mod exchange;
-mod strategy;
-mod risk;
-
 use anyhow::Result;
-use tracing::info;
-use tokio::sync::mpsc;
-use std::collections::BTreeMap;
 
-use crate::{config::ArbCfg, models::*};
+use hft_core::*;
+use hft_index_arb::{config::ArbCfg, exchange, risk, strategy::BasisArb};
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -17,60 +10,53 @@ async fn main() -> Result<()> {
     let cfg = ArbCfg::load()?;
 
     // channels
-    let (md_tx, mut md_rx)   = mpsc::channel::<Tick>(4096);
-    let (fill_tx, mut fills) = mpsc::channel::<Fill>(4096);
-    let (od_tx, od_rx)       = mpsc::channel::<Order>(4096);
+    let (venue, engine) = link(4096);
+
+    // start sim (or replay a recording)
+    let clock  = Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks);
+    let origin = clock.start();
+    match (&cfg.replay, &cfg.data) {
+        (Some(path), _) => {
+            let rp = Replay::open(path, venue.md_tx, venue.er_tx)?.fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, Some(path)) => {
+            let mut syms: Vec<&str> = cfg.weights.keys().map(String::as_str).collect();
+            syms.push(cfg.etf_sym());
+            let md = history::load(path, &syms, origin)?;
+            let rp = Replay::new(md, venue.md_tx, venue.er_tx).fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, None) => {
+            tokio::spawn(
+                exchange::Exchange::new(venue.md_tx, venue.er_tx, &cfg.weights, 100.0, cfg.seed)
+                    .fees(cfg.fees.clone())
+                    .run(clock, venue.od_rx, venue.done_rx),
+            );
+        }
+    }
 
-    // start sim
-    tokio::spawn(
-        exchange::Exchange::new(md_tx, fill_tx, &cfg.weights, 100.0)
-            .run(cfg.tick_ms, od_rx),
-    );
+    // end-of-run report
+    let mut perf = Performance::new("hft-index-arb", Duration::from_millis(cfg.report_bucket_ms));
+    if let Some(path) = &cfg.report_json {
+        perf = perf.json(path);
+    }
 
     // strategy + risk
-    let mut strat = strategy::BasisArb::new(cfg.clone());
-    let riskmgr   = risk::Risk::new(cfg.clone());
-
-    // latest price cache
-    let mut px_map: BTreeMap<String, f64> = cfg
-        .weights
-        .keys()
-        .cloned()
-        .map(|s| (s, 100.0))
-        .collect();
-    px_map.insert(cfg.etf_sym().into(), 100.0);
-
-    loop {
-        tokio::select! {
-            Some(t) = md_rx.recv() => {
-                px_map.insert(t.symbol.clone(), t.px);
-
-                // invoke strategy when ETF tick arrives (ensures all stocks updated first)
-                if t.symbol == cfg.etf_sym() {
-                    let orders = strat.on_tick(t.px, &px_map);
-                    for o in orders {
-                        // inventory after hypothetical fill
-                        let inv_after = if o.symbol == cfg.etf_sym() {
-                            strat.pos_etf() + if matches!(o.side,Side::Buy){o.qty}else{-o.qty}
-                        } else {
-                            strat.pos_stock(&o.symbol) + if matches!(o.side,Side::Buy){o.qty}else{-o.qty}
-                        };
-
-                        if riskmgr.allow(&o.symbol, inv_after) {
-                            od_tx.send(o).await?;
-                        }
-                    }
-                }
-            }
-
-            Some(f) = fills.recv() => {
-                strat.on_fill(&f);
-                info!("FILL {:?} {:.0} {} @ {:.2}", f.side, f.qty, f.symbol, f.px);
-            }
-
-            else => break,
-        }
+    let strat   = BasisArb::new(cfg.clone());
+    let riskmgr = risk::Risk::new(cfg.clone());
+
+    let mut engine = Engine::new(strat, engine)
+        .risk(move |strat: &BasisArb, req: &OrderReq, _| match req {
+            // inventory after hypothetical fill
+            OrderReq::New(o) => riskmgr.allow(&o.symbol, strat.pos_after(o)),
+            _ => true,
+        })
+        .report(perf);
+    if let Some(path) = &cfg.record {
+        engine = engine.record(Recorder::create(path, origin)?);
     }
+    engine.run().await;
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-use crate::{config::ArbCfg, models::*};
+use crate::config::ArbCfg;
 
 pub struct Risk { cfg: ArbCfg }
 
@@ -1,18 +1,30 @@
-use crate::{config::ArbCfg, models::*};
-use ndarray::Array1;
+use crate::config::ArbCfg;
+use hft_core::*;
 use std::collections::BTreeMap;
+use tracing::{info, warn};
 
 pub struct BasisArb {
     cfg: ArbCfg,
-    // rolling basis list (bps)
-    basis_hist: Vec<f64>,
+    // rolling basis window (bps)
+    basis_hist: Rolling,
     // live positions
     pos_etf: f64,
     pos_stock: BTreeMap<String, f64>,
+    ids: OrderIds,
+    // latest price cache, ETF included
+    px_map: BTreeMap<String, f64>,
 }
 
 impl BasisArb {
     pub fn new(cfg: ArbCfg) -> Self {
+        let mut px_map: BTreeMap<String, f64> = cfg
+            .weights
+            .keys()
+            .cloned()
+            .map(|s| (s, 100.0))
+            .collect();
+        px_map.insert(cfg.etf_sym().into(), 100.0);
+
         Self {
             pos_etf: 0.0,
             pos_stock: cfg
@@ -20,7 +32,9 @@ impl BasisArb {
                 .into_iter()
                 .map(|s| (s, 0.0))
                 .collect(),
-            basis_hist: Vec::with_capacity(cfg.lookback),
+            basis_hist: Rolling::new(cfg.lookback),
+            ids: OrderIds::default(),
+            px_map,
             cfg,
         }
     }
@@ -28,6 +42,16 @@ impl BasisArb {
     pub fn pos_etf(&self) -> f64 { self.pos_etf }
     pub fn pos_stock(&self, sym:&str) -> f64 { self.pos_stock[sym] }
 
+    /// Inventory in `o.symbol` if `o` fills completely (risk-check helper).
+    pub fn pos_after(&self, o: &Order) -> f64 {
+        let pos = if o.symbol == self.cfg.etf_sym() {
+            self.pos_etf
+        } else {
+            self.pos_stock.get(&o.symbol).copied().unwrap_or(0.0)
+        };
+        pos + o.side.sign() * o.qty
+    }
+
     /// Given latest prices, decide orders.
     pub fn on_tick(
         &mut self,
@@ -44,19 +68,15 @@ impl BasisArb {
         let basis_bps = (etf_px / fair - 1.0) * 10_000.0; // convert to bps
 
         // roll window
-        if self.basis_hist.len() == self.cfg.lookback {
-            self.basis_hist.remove(0);
-        }
         self.basis_hist.push(basis_bps);
 
-        if self.basis_hist.len() < self.cfg.lookback {
+        if !self.basis_hist.is_full() {
             return vec![];
         }
 
         // z-score of basis (mean≈0 by construction)
-        let arr = Array1::from(self.basis_hist.clone());
-        let std = arr.std(0.0).max(1e-4); // avoid div-by-0
-        let z = basis_bps / std;
+        let std = self.basis_hist.std().max(1e-4); // avoid div-by-0
+        let _z = basis_bps / std;
 
         let mut orders = Vec::new();
 
@@ -65,37 +85,21 @@ impl BasisArb {
             && self.pos_etf - self.cfg.size_etf >= -self.cfg.pos_limit
         {
             // ETF rich → SELL ETF, BUY basket
-            orders.push(Order {
-                symbol: self.cfg.etf_sym().into(),
-                px: etf_px,
-                qty: self.cfg.size_etf,
-                side: Side::Sell,
-            });
+            orders.push(Order::market(
+                self.ids.next(), self.cfg.etf_sym(), Side::Sell, self.cfg.size_etf,
+            ));
             for (s, w) in &self.cfg.weights {
-                orders.push(Order {
-                    symbol: s.clone(),
-                    px: stock_px[s],
-                    qty: self.cfg.size_etf * w,
-                    side: Side::Buy,
-                });
+                orders.push(Order::market(self.ids.next(), s.as_str(), Side::Buy, self.cfg.size_etf * w));
             }
         } else if basis_bps < -self.cfg.entry_bp
             && self.pos_etf + self.cfg.size_etf <= self.cfg.pos_limit
         {
             // ETF cheap → BUY ETF, SELL basket
-            orders.push(Order {
-                symbol: self.cfg.etf_sym().into(),
-                px: etf_px,
-                qty: self.cfg.size_etf,
-                side: Side::Buy,
-            });
+            orders.push(Order::market(
+                self.ids.next(), self.cfg.etf_sym(), Side::Buy, self.cfg.size_etf,
+            ));
             for (s, w) in &self.cfg.weights {
-                orders.push(Order {
-                    symbol: s.clone(),
-                    px: stock_px[s],
-                    qty: self.cfg.size_etf * w,
-                    side: Side::Sell,
-                });
+                orders.push(Order::market(self.ids.next(), s.as_str(), Side::Sell, self.cfg.size_etf * w));
             }
         }
 
@@ -103,23 +107,15 @@ impl BasisArb {
         if basis_bps.abs() < self.cfg.exit_bp && self.pos_etf.abs() > 0.0 {
             // close ETF
             let side_etf = if self.pos_etf > 0.0 { Side::Sell } else { Side::Buy };
-            orders.push(Order {
-                symbol: self.cfg.etf_sym().into(),
-                px: etf_px,
-                qty: self.pos_etf.abs(),
-                side: side_etf,
-            });
+            orders.push(Order::market(
+                self.ids.next(), self.cfg.etf_sym(), side_etf, self.pos_etf.abs(),
+            ));
             // close each stock
-            for (s, p) in stock_px {
+            for s in stock_px.keys() {
                 let inv = self.pos_stock[s];
                 if inv != 0.0 {
                     let side = if inv > 0.0 { Side::Sell } else { Side::Buy };
-                    orders.push(Order {
-                        symbol: s.clone(),
-                        px: *p,
-                        qty: inv.abs(),
-                        side,
-                    });
+                    orders.push(Order::market(self.ids.next(), s.as_str(), side, inv.abs()));
                 }
             }
         }
@@ -128,8 +124,8 @@ impl BasisArb {
     }
 
     /// Update position book.
-    pub fn on_fill(&mut self, f: &Fill) {
-        let delta = if matches!(f.side, Side::Buy) { f.qty } else { -f.qty };
+    fn on_fill(&mut self, f: &Fill) {
+        let delta = f.side.sign() * f.qty;
 
         if f.symbol == self.cfg.etf_sym() {
             self.pos_etf += delta;
@@ -138,3 +134,30 @@ impl BasisArb {
         }
     }
 }
+
+impl Strategy for BasisArb {
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        let MarketData::Tick(t) = md else { return Vec::new() };
+        self.px_map.insert(t.symbol.clone(), t.mid());
+
+        // invoke the signal when the ETF ticks (all stocks are updated first)
+        if t.symbol != self.cfg.etf_sym() {
+            return Vec::new();
+        }
+        let px_map = self.px_map.clone();
+        self.on_tick(t.mid(), &px_map).into_iter().map(OrderReq::New).collect()
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        match er {
+            ExecReport::Fill(f) => {
+                self.on_fill(f);
+                info!("FILL #{} {:?} {:.0} {} @ {:.2} fee={:.4} leaves={:.0}",
+                      f.id, f.side, f.qty, f.symbol, f.px, f.fee, f.leaves);
+            }
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            _ => {}
+        }
+        Vec::new()
+    }
+}
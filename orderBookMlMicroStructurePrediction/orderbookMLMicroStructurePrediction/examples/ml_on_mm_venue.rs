@@ -0,0 +1,42 @@
+// MIT © 2025
+//! The depth-imbalance ML trader run against the passive-MM price-time
+//! priority venue instead of its own synthetic stream — no new `main.rs`,
+//! just a different venue on the other end of the engine link.
+//!
+//! `cargo run -p hft-ml-lob --example ml_on_mm_venue`
+
+use hft_core::*;
+use hft_ml_lob::{config::Cfg, strategy::MLTrader};
+use hft_passive_mm::exchange::Exchange;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let cfg = Cfg {
+        symbol:        "SIMPERP".into(),
+        tick_sz:       0.01,
+        tick_ms:       50,
+        seed:          42,
+        backtest:      true,
+        max_ticks:     Some(2_000),
+        record:        None,
+        replay:        None,
+        data:          None,
+        report_bucket_ms: 1000,
+        report_json:   None,
+        fees:          FeeSchedule::default(),
+        theta:         0.10,
+        learning_rate: 0.02,
+        max_pos:       50.0,
+        max_orders_s:  20,
+    };
+
+    let (venue, engine) = link(1024);
+    let ex = Exchange::new(venue.md_tx, venue.er_tx, &cfg.symbol, 100.0, cfg.tick_sz, cfg.seed);
+    tokio::spawn(ex.run(Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks), venue.od_rx, venue.done_rx));
+
+    let perf = Performance::new("ml_on_mm_venue", Duration::from_millis(cfg.report_bucket_ms));
+    Engine::new(MLTrader::new(cfg), engine).report(perf).run().await;
+}
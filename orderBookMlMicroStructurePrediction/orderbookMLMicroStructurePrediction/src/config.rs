@@ -1,5 +1,6 @@
 // MIT © 2025
 use anyhow::Result;
+use hft_core::FeeSchedule;
 use figment::providers::{Env, Format, Toml};
 use serde::Deserialize;
 
@@ -8,6 +9,17 @@ pub struct Cfg {
     pub symbol:       String,
     pub tick_sz:      f64,
     pub tick_ms:      u64,
+    pub seed:         u64,
+    #[serde(default)]
+    pub backtest:     bool,         // virtual clock, no wall-clock pacing
+    pub max_ticks:    Option<u64>,  // end the session after N ticks
+    pub record:       Option<String>, // JSONL of market data + executions
+    pub replay:       Option<String>, // replay a recording instead of the sim
+    pub data:         Option<String>, // historical L2 CSV/Parquet instead of the sim
+    pub report_bucket_ms: u64,        // Sharpe/Sortino bucket of the end-of-run report
+    pub report_json:  Option<String>, // also write the report as JSON
+    #[serde(default)]
+    pub fees:         FeeSchedule,    // venue maker/taker bps, per-unit, volume tiers
 
     pub theta:        f64,
     pub learning_rate:f64,
@@ -18,8 +30,10 @@ pub struct Cfg {
 
 impl Cfg {
     pub fn load() -> Result<Self> {
-        Ok(figment::Figment::from(Toml::file("Config.toml"))
+        let cfg: Self = figment::Figment::from(Toml::file("Config.toml"))
             .merge(Env::prefixed("MLLOB_"))
-            .extract()?)
+            .extract()?;
+        anyhow::ensure!(cfg.report_bucket_ms > 0, "report_bucket_ms must be positive");
+        Ok(cfg)
     }
 }
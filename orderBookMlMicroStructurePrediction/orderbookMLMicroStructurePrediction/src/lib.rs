@@ -0,0 +1,8 @@
+// MIT © 2025
+//! Online logistic-regression trader on 5-level depth imbalance, plus the
+//! synthetic order-book stream it learns from.
+
+pub mod config;
+pub mod risk;
+pub mod simulator;
+pub mod strategy;
@@ -1,43 +1,55 @@
 // MIT © 2025
-mod config;
-mod models;
-mod simulator;
-mod strategy;
-mod risk;
-
 use anyhow::Result;
 use tracing::info;
-use tokio::sync::mpsc;
-use models::*;
+use hft_core::*;
+use hft_ml_lob::{config, risk, simulator, strategy::MLTrader};
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     let cfg = config::Cfg::load()?;
+    info!("trading {} theta={} lr={}", cfg.symbol, cfg.theta, cfg.learning_rate);
 
     /* channels */
-    let (book_tx, mut books) = mpsc::channel::<Book>(1024);
-    let (ord_tx,  mut ord_rx)= mpsc::channel::<Order>(1024);
-    let (fill_tx, mut fills) = mpsc::channel::<Fill>(1024);
+    let (venue, engine) = link(1024);
+
+    /* simulator, or a recorded session */
+    let clock  = Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks);
+    let origin = clock.start();
+    match (&cfg.replay, &cfg.data) {
+        (Some(path), _) => {
+            let rp = Replay::open(path, venue.md_tx, venue.er_tx)?.fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, Some(path)) => {
+            let md = history::load(path, &[cfg.symbol.as_str()], origin)?;
+            let rp = Replay::new(md, venue.md_tx, venue.er_tx).fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, None) => {
+            tokio::spawn(simulator::run(cfg.clone(), clock, venue.md_tx, venue.od_rx, venue.er_tx, venue.done_rx));
+        }
+    }
 
-    tokio::spawn(simulator::run(cfg.clone(), book_tx, ord_rx, fill_tx));
+    /* end-of-run report */
+    let mut perf = Performance::new("hft-ml-lob", Duration::from_millis(cfg.report_bucket_ms));
+    if let Some(path) = &cfg.report_json {
+        perf = perf.json(path);
+    }
 
-    let mut strat = strategy::MLTrader::new(cfg.clone());
-    let mut risk  = risk::Risk::new(&cfg);
+    let strat    = MLTrader::new(cfg.clone());
+    let mut risk = risk::Risk::new(&cfg);
 
-    loop {
-        tokio::select! {
-            Some(book) = books.recv() => {
-                if let Some(o) = strat.on_book(&book) {
-                    if risk.allow(&o) { ord_tx.send(o).await?; }
-                }
-            }
-            Some(f) = fills.recv() => {
-                strat.on_fill(&f);
-                info!("FILL {:?} {:.0} @ {:.2}", f.side, f.qty, f.px);
-            }
-            else => break,
-        }
+    let mut engine = Engine::new(strat, engine)
+        .risk(move |_: &MLTrader, req: &OrderReq, now| match req {
+            OrderReq::New(o) => risk.allow(o, now),
+            _ => true,
+        })
+        .report(perf);
+    if let Some(path) = &cfg.record {
+        engine = engine.record(Recorder::create(path, origin)?);
     }
+    engine.run().await;
     Ok(())
 }
@@ -1,53 +1,103 @@
 // MIT © 2025
 //! Synthetic 5-level order-book stream & naive fill engine.
 
-use crate::{config::Cfg, models::*};
-use rand::random;
+use crate::config::Cfg;
+use hft_core::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
 use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
+use tracing::info;
 
 pub async fn run(
     cfg: Cfg,
-    book_tx: mpsc::Sender<Book>,
-    mut ord_rx: mpsc::Receiver<Order>,
-    fill_tx: mpsc::Sender<Fill>,
+    mut clock: Clock,
+    book_tx: mpsc::Sender<MarketData>,
+    mut ord_rx: mpsc::Receiver<OrderReq>,
+    er_tx: mpsc::Sender<ExecReport>,
+    mut done_rx: mpsc::Receiver<()>,     // one per book, once the strategy has reacted
 ) {
     let mut mid = 100.00;
-    let mut clock = interval(Duration::from_millis(cfg.tick_ms));
+    let mut rng = StdRng::seed_from_u64(cfg.seed);     // sole source of randomness
+    let mut ts = Instant::now();
+    let mut fees = FeeMeter::new(cfg.fees.clone());
 
     loop {
         tokio::select! {
+            // requests already queued are handled before the next tick
+            biased;
+
+            /* -------- order from ML strategy -------- */
+            Some(req) = ord_rx.recv() => {
+                for er in on_request(&cfg, &mut fees, mid, ts, req) {
+                    let _ = er_tx.send(er).await;
+                }
+            }
+
             /* -------- 1-ms synthetic book update -------- */
-            _ = clock.tick() => {
+            now = clock.tick() => {
+                // session time, not wall time
+                let Some(now) = now else { break };
+                ts = now;
+
                 // random-walk mid-price
-                mid += if random::<f64>() > 0.5 { cfg.tick_sz } else { -cfg.tick_sz };
+                mid += if rng.gen::<f64>() > 0.5 { cfg.tick_sz } else { -cfg.tick_sz };
 
                 // random depths for five price levels
-                let mut bid = [0.0; 5];
-                let mut ask = [0.0; 5];
-                for i in 0..5 {
-                    bid[i] = 500.0 + random::<f64>() * 1000.0;
-                    ask[i] = 500.0 + random::<f64>() * 1000.0;
+                let mut bid = [0.0; DEPTH];
+                let mut ask = [0.0; DEPTH];
+                for i in 0..DEPTH {
+                    bid[i] = 500.0 + rng.gen::<f64>() * 1000.0;
+                    ask[i] = 500.0 + rng.gen::<f64>() * 1000.0;
                 }
 
-                let book = Book { bid_vol: bid, ask_vol: ask, mid };
-                let _ = book_tx.send(book).await;          // await after RNG is dropped
-            }
+                let book = Book { symbol: cfg.symbol.clone(), bid_vol: bid, ask_vol: ask, mid, ts };
+                let _ = book_tx.send(MarketData::Book(book)).await;
 
-            /* -------- order from ML strategy -------- */
-            Some(o) = ord_rx.recv() => {
-                let px  = if o.side == Side::Buy { mid + cfg.tick_sz }
-                                           else { mid - cfg.tick_sz };
-                let fill = Fill {
-                    px,
-                    qty:  o.qty,
-                    side: o.side,
-                    ts:   tokio::time::Instant::now().into_std(),
-                };
-                let _ = fill_tx.send(fill).await;
+                // lockstep: let the strategy react and take in what it sent
+                // before the next update, so a seeded run is identical however
+                // fast either side is
+                if done_rx.recv().await.is_none() { break; }
+                while let Ok(req) = ord_rx.try_recv() {
+                    for er in on_request(&cfg, &mut fees, mid, ts, req) {
+                        let _ = er_tx.send(er).await;
+                    }
+                }
             }
 
             else => break,
         }
     }
+    info!("session over after {} ticks", clock.ticks());
+}
+
+/// Orders fill one tick through the mid on arrival, so there is never
+/// anything left resting to cancel or amend.
+fn on_request(cfg: &Cfg, fees: &mut FeeMeter, mid: f64, ts: Instant, req: OrderReq) -> Vec<ExecReport> {
+    match req {
+        OrderReq::New(o) if o.symbol != cfg.symbol => {
+            vec![ExecReport::Reject { id: o.id, reason: "unknown symbol".into() }]
+        }
+        OrderReq::New(o) => {
+            let px = if o.side == Side::Buy { mid + cfg.tick_sz }
+                                       else { mid - cfg.tick_sz };
+            vec![
+                ExecReport::NewOrderAck { id: o.id },
+                ExecReport::Fill(fees.charge(Fill {
+                    id:     o.id,
+                    symbol: o.symbol,
+                    px,
+                    qty:    o.qty,
+                    side:   o.side,
+                    ts,
+                    leaves: 0.0,
+                    liquidity: Liquidity::Taker,
+                    fee:    0.0,
+                })),
+            ]
+        }
+        OrderReq::Cancel { id } | OrderReq::Replace { id, .. } => {
+            vec![ExecReport::Reject { id, reason: "unknown or closed order".into() }]
+        }
+    }
 }
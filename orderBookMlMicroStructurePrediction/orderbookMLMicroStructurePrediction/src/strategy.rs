@@ -1,7 +1,9 @@
 // MIT © 2025
 //! Online logistic regression using 5-level depth imbalance as features.
 
-use crate::{config::Cfg, models::*};
+use crate::config::Cfg;
+use hft_core::*;
+use tracing::{info, warn};
 
 pub struct MLTrader {
     cfg: Cfg,
@@ -10,6 +12,7 @@ pub struct MLTrader {
     last_feat: [f64; 5],
     last_mid:  f64,
     have_prev: bool,
+    ids:       OrderIds,
 }
 
 impl MLTrader {
@@ -21,6 +24,7 @@ impl MLTrader {
             last_feat: [0.0; 5],
             last_mid: 0.0,
             have_prev: false,
+            ids: OrderIds::default(),
         }
     }
 
@@ -28,10 +32,10 @@ impl MLTrader {
     pub fn on_book(&mut self, book: &Book) -> Option<Order> {
         /* -------- build feature vector -------- */
         let mut x = [0.0; 5];
-        for i in 0..5 {
+        for (i, xi) in x.iter_mut().enumerate() {
             let num = book.bid_vol[i] - book.ask_vol[i];
             let den = book.bid_vol[i] + book.ask_vol[i];
-            x[i] = if den > 0.0 { num / den } else { 0.0 };  // imbalance ∈ [-1,1]
+            *xi = if den > 0.0 { num / den } else { 0.0 };   // imbalance ∈ [-1,1]
         }
 
         /* -------- forward pass -------- */
@@ -56,9 +60,9 @@ impl MLTrader {
 
         /* -------- trade when confident -------- */
         if  p_up - 0.5 >  self.cfg.theta {
-            Some(Order { side: Side::Buy,  qty: 1000.0 })
+            Some(Order::market(self.ids.next(), &self.cfg.symbol, Side::Buy,  1000.0))
         } else if 0.5 - p_up > self.cfg.theta {
-            Some(Order { side: Side::Sell, qty: 1000.0 })
+            Some(Order::market(self.ids.next(), &self.cfg.symbol, Side::Sell, 1000.0))
         } else {
             None
         }
@@ -67,6 +71,28 @@ impl MLTrader {
     pub fn on_fill(&mut self, _f: &Fill) {}
 }
 
+impl Strategy for MLTrader {
+    /// Learns from and trades on depth snapshots; ticks and prints are ignored.
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        match md {
+            MarketData::Book(book) => self.on_book(book).map(OrderReq::New).into_iter().collect(),
+            MarketData::Tick(_) | MarketData::Trade(_) => Vec::new(),
+        }
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        match er {
+            ExecReport::Fill(f) => {
+                self.on_fill(f);
+                info!("FILL #{} {:?} {:.0} @ {:.2} fee={:.4} leaves={:.0}", f.id, f.side, f.qty, f.px, f.fee, f.leaves);
+            }
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            _ => {}
+        }
+        Vec::new()
+    }
+}
+
 #[inline]
 fn sigmoid(z: f64) -> f64 {
     1.0 / (1.0 + (-z).exp())
@@ -0,0 +1,48 @@
+//! Cost of one `PairTrader::on_ticks` across lookbacks and β estimators;
+//! with the rolling window it should not grow with the lookback.
+//!
+//! `cargo bench -p hft-statarb --bench on_ticks` (reads `Config.toml` from
+//! the crate directory)
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use hft_statarb::config::{BetaMode, StratCfg};
+use hft_statarb::strategy::PairTrader;
+
+const LOOKBACKS: [usize; 4] = [32, 256, 2048, 16384];
+
+/// Move both legs by a bounded, repeating wiggle.
+fn step(k: u64, a: &mut f64, b: &mut f64) {
+    let wiggle = ((k * 7919) % 13) as f64 - 6.0;
+    *a *= 1.0 + wiggle * 1e-4;
+    *b *= 1.0 + wiggle * 0.9e-4;
+}
+
+fn on_ticks(c: &mut Criterion) {
+    let base = StratCfg::load().expect("Config.toml in the crate directory");
+    for mode in [BetaMode::Fixed, BetaMode::RollingOls, BetaMode::Kalman] {
+        let mut g = c.benchmark_group(format!("on_ticks_{mode:?}"));
+        for look in LOOKBACKS {
+            // entries never trigger, so only the signal is timed
+            let cfg = StratCfg { lookback: look, beta_mode: mode, entry_z: f64::INFINITY, ..base.clone() };
+            let mut pt = PairTrader::new(cfg);
+            let (mut a, mut b) = (100.0, 98.0);
+            let mut k: u64 = 0;
+            for _ in 0..look {
+                step(k, &mut a, &mut b);
+                k += 1;
+                pt.on_ticks(a, b);
+            }
+            g.bench_with_input(BenchmarkId::from_parameter(look), &look, |bench, _| {
+                bench.iter(|| {
+                    step(k, &mut a, &mut b);
+                    k += 1;
+                    black_box(pt.on_ticks(a, b))
+                });
+            });
+        }
+        g.finish();
+    }
+}
+
+criterion_group!(benches, on_ticks);
+criterion_main!(benches);
@@ -0,0 +1,296 @@
+//! N-leg basket traded on its eigenportfolio: the weights `w` are the
+//! eigenvector of the smallest eigenvalue of the covariance of the legs'
+//! log prices over the lookback window, i.e. the combination
+//!
+//! ```text
+//! spread = Σ w_i · log P_i        w_0 = 1
+//! ```
+//!
+//! that varies least, and so reverts hardest. The basket is sold when the
+//! spread is `entry_z` deviations rich, bought when cheap, and flattened
+//! inside `exit_z`, just like a pair. The weights are refitted only while
+//! the basket is flat, so an open position is always judged on the
+//! combination it was entered with.
+
+use crate::config::{BasketCfg, StratCfg};
+use hft_core::*;
+use ndarray::Array2;
+use tracing::{info, warn};
+
+pub struct Basket {
+    symbols:   Vec<String>,
+    entry_z:   f64,
+    exit_z:    f64,
+    size:      f64,
+    pos_limit: f64,
+    // rolling window of the legs' log prices
+    window:  RollingCov,
+    // latest price per leg; the signal runs once every leg has ticked again
+    last:    Vec<f64>,
+    fresh:   Vec<bool>,
+    // live inventory per leg
+    pos:     Vec<f64>,
+    weights: Vec<f64>,
+    ids:     OrderIds,
+}
+
+/* ----- public helpers for outside code ----- */
+impl Basket {
+    pub fn symbols(&self) -> &[String] { &self.symbols }
+    pub fn weights(&self) -> &[f64] { &self.weights }
+    pub fn pos(&self) -> &[f64] { &self.pos }
+}
+
+/* ----- core logic ----- */
+impl Basket {
+    /// `b` with anything it leaves out taken from the top level of `cfg`.
+    pub fn new(cfg: &StratCfg, b: &BasketCfg) -> Self {
+        let n = b.symbols.len();
+        Self {
+            symbols:   b.symbols.clone(),
+            entry_z:   b.entry_z.unwrap_or(cfg.entry_z),
+            exit_z:    b.exit_z.unwrap_or(cfg.exit_z),
+            size:      b.size.unwrap_or(cfg.size),
+            pos_limit: cfg.pos_limit,
+            window:    RollingCov::new(n, b.lookback.unwrap_or(cfg.lookback)),
+            last:      vec![0.0; n],
+            fresh:     vec![false; n],
+            pos:       vec![0.0; n],
+            weights:   Vec::new(),
+            ids:       OrderIds::default(),
+        }
+    }
+
+    /// Number orders from `ids`, so several traders can share one venue.
+    pub fn ids(mut self, ids: OrderIds) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Consume the latest price of every leg, produce zero or more orders.
+    pub fn on_prices(&mut self, px: &[f64]) -> Vec<Order> {
+        let logs: Vec<f64> = px.iter().map(|p| p.ln()).collect();
+        self.window.push(&logs);
+        if !self.window.is_full() {
+            return vec![];
+        }
+
+        let flat = self.pos.iter().all(|&p| p == 0.0);
+        if flat || self.weights.is_empty() {
+            match eigenportfolio(&self.window) {
+                Some(w) => self.weights = w,
+                None if self.weights.is_empty() => return vec![],
+                None => {}
+            }
+        }
+
+        // current spread Z-score, from the window's moments under the weights
+        let (w, win) = (&self.weights, &self.window);
+        let legs = 0..w.len();
+        let spread = legs.clone().map(|i| w[i] * logs[i]).sum::<f64>();
+        let mean   = legs.clone().map(|i| w[i] * win.mean(i)).sum::<f64>();
+        let var    = legs.clone().flat_map(|i| legs.clone().map(move |j| (i, j)))
+            .map(|(i, j)| w[i] * w[j] * win.cov(i, j))
+            .sum::<f64>();
+        let std    = var.max(0.0).sqrt().max(1e-8);
+        let z      = (spread - mean) / std;
+
+        let mut orders = Vec::new();
+
+        // ----- entry logic: the first leg counts basket units -----
+        if z > self.entry_z && self.pos[0] - self.size >= -self.pos_limit {
+            // short basket → sell w, so sell where w > 0 and buy where w < 0
+            self.trade(-self.size, &mut orders);
+        } else if z < -self.entry_z && self.pos[0] + self.size <= self.pos_limit {
+            self.trade(self.size, &mut orders);
+        }
+
+        // ----- exit / flatten logic -----
+        if z.abs() < self.exit_z && !flat {
+            for k in 0..self.symbols.len() {
+                if self.pos[k] != 0.0 {
+                    let side = if self.pos[k] > 0.0 { Side::Sell } else { Side::Buy };
+                    orders.push(Order::market(self.ids.next(), &self.symbols[k], side, self.pos[k].abs()));
+                }
+            }
+        }
+
+        orders
+    }
+
+    /// Market orders for `units` of the basket, leg `k` scaled by its weight.
+    fn trade(&mut self, units: f64, orders: &mut Vec<Order>) {
+        for (sym, w) in self.symbols.iter().zip(&self.weights) {
+            let qty = units * w;
+            if qty.abs() < 1e-9 {
+                continue;
+            }
+            let side = if qty > 0.0 { Side::Buy } else { Side::Sell };
+            orders.push(Order::market(self.ids.next(), sym, side, qty.abs()));
+        }
+    }
+
+    /// Log the fitted weights and what is held, at the end of the session.
+    pub fn log_report(&self) {
+        let legs: Vec<String> = self.symbols.iter().zip(&self.weights).zip(&self.pos)
+            .map(|((s, w), p)| format!("{s}:{w:+.4}({p:+.2})"))
+            .collect();
+        info!("BASKET weights(pos) {}", legs.join(" "));
+    }
+}
+
+/// Smallest share of the unit-length eigenvector the first leg must hold;
+/// below it the other legs would trade many times the basket size.
+const MIN_LEAD_WEIGHT: f64 = 0.1;
+
+/// Weights of the least-variance combination of the window's series,
+/// scaled so the first leg has weight one; `None` when the first leg plays
+/// too small a part in it (no leg then trades more than 1 / `MIN_LEAD_WEIGHT`
+/// basket units).
+fn eigenportfolio(window: &RollingCov) -> Option<Vec<f64>> {
+    let n = window.series();
+    let cov = Array2::from_shape_fn((n, n), |(i, j)| window.cov(i, j));
+
+    let (values, vectors) = jacobi(cov);
+    let min = (0..n).min_by(|&a, &b| values[a].total_cmp(&values[b]))?;
+    let w0 = vectors[[0, min]];
+    // the columns are unit length, so this also bounds every other weight
+    if w0.abs() < MIN_LEAD_WEIGHT {
+        return None;
+    }
+    Some((0..n).map(|k| vectors[[k, min]] / w0).collect())
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix by
+/// cyclic Jacobi rotations.
+fn jacobi(mut a: Array2<f64>) -> (Vec<f64>, Array2<f64>) {
+    let n = a.nrows();
+    let mut v = Array2::<f64>::eye(n);
+    for _sweep in 0..50 {
+        let off: f64 = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[[i, j]].powi(2))
+            .sum();
+        if off < 1e-30 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]].abs() < 1e-300 {
+                    continue;
+                }
+                // rotation angle that zeroes a[p][q]
+                let theta = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[[k, p]], v[[k, q]]);
+                    v[[k, p]] = c * vkp - s * vkq;
+                    v[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[[i, i]]).collect(), v)
+}
+
+impl Strategy for Basket {
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        let MarketData::Tick(tick) = md else { return Vec::new() };
+        let Some(k) = self.symbols.iter().position(|s| *s == tick.symbol) else { return Vec::new() };
+        self.last[k] = tick.mid();
+        self.fresh[k] = true;
+
+        // run the signal only once every leg has a new price
+        if !self.fresh.iter().all(|&f| f) {
+            return Vec::new();
+        }
+        self.fresh.fill(false);
+        let px = self.last.clone();
+        self.on_prices(&px).into_iter().map(OrderReq::New).collect()
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        match er {
+            ExecReport::Fill(fill) => {
+                if let Some(k) = self.symbols.iter().position(|s| *s == fill.symbol) {
+                    self.pos[k] += fill.side.sign() * fill.qty;
+                }
+                info!("FILL #{} {:?} {:.2} {} @ {:.2} fee={:.4} leaves={:.2}",
+                      fill.id, fill.side, fill.qty, fill.symbol, fill.px, fill.fee, fill.leaves);
+            }
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            _ => {}
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rand_distr::StandardNormal;
+
+    #[test]
+    fn jacobi_diagonalises_a_known_matrix() {
+        // eigenvalues 1, 3, 5 with eigenvectors (1,−1,0)/√2, (1,1,0)/√2, (0,0,1)
+        let a = Array2::from_shape_vec((3, 3), vec![2.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 5.0]).unwrap();
+        let (values, vectors) = jacobi(a.clone());
+
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        for (got, want) in sorted.iter().zip([1.0, 3.0, 5.0]) {
+            assert!((got - want).abs() < 1e-12, "{values:?}");
+        }
+        for (k, &value) in values.iter().enumerate() {
+            let v = vectors.column(k);
+            assert!((&a.dot(&v) - &(&v * value)).iter().all(|d| d.abs() < 1e-12));
+            assert!((v.dot(&v) - 1.0).abs() < 1e-12);
+        }
+        let min = (0..3).min_by(|&i, &j| values[i].total_cmp(&values[j])).unwrap();
+        let v = vectors.column(min);
+        assert!((v[0] + v[1]).abs() < 1e-12 && v[2].abs() < 1e-12 && (v[0].abs() - 0.5f64.sqrt()).abs() < 1e-12);
+    }
+
+    /// A window of `n` rows from `row(z0, z1, z2)` on standard normal draws.
+    fn window(n: usize, row: impl Fn(f64, f64, f64) -> [f64; 3]) -> RollingCov {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut w = RollingCov::new(3, n);
+        for _ in 0..n {
+            let mut z = || rng.sample::<f64, _>(StandardNormal);
+            let (z0, z1, z2) = (z(), z(), z());
+            w.push(&row(z0, z1, z2));
+        }
+        w
+    }
+
+    #[test]
+    fn basket_weights_hedge_the_tied_legs() {
+        // legs 0 and 1 move together, leg 2 on its own
+        let w = window(2_000, |z0, z1, z2| [z0, z0 + 0.01 * z1, z2]);
+        let weights = eigenportfolio(&w).unwrap();
+        assert!((weights[0] - 1.0).abs() < 1e-12);
+        assert!((weights[1] + 1.0).abs() < 0.01, "{weights:?}");
+        assert!(weights[2].abs() < 0.01, "{weights:?}");
+    }
+
+    #[test]
+    fn no_weights_when_the_first_leg_barely_takes_part() {
+        // the quiet combination is legs 1 and 2 alone; scaling it to leg 0
+        // would blow the other legs up
+        let w = window(2_000, |z0, z1, z2| [z0, z1, z1 + 0.01 * z2]);
+        assert_eq!(eigenportfolio(&w), None);
+    }
+}
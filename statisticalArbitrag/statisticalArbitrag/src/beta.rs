@@ -0,0 +1,185 @@
+//! Hedge ratio of leg A on leg B, in log prices:
+//!
+//! ```text
+//! log P_A = α + β·log P_B + ε
+//! ```
+//!
+//! * **fixed** — `beta` from the config, α = 0;
+//! * **rolling OLS** — least squares over the lookback window;
+//! * **Kalman** — (α, β) as a random walk, updated by every new pair of
+//!   prices: state noise `δ/(1−δ)` per step, observation noise `obs_var`.
+
+use crate::config::{BetaMode, KalmanCfg, StratCfg};
+use hft_core::RollingCov;
+
+/// Current estimate of the pair relationship.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub alpha: f64,
+    pub beta:  f64,
+}
+
+pub struct HedgeRatio {
+    mode:   BetaMode,
+    fit:    Fit,
+    kalman: Kalman,
+}
+
+impl HedgeRatio {
+    pub fn new(cfg: &StratCfg) -> Self {
+        Self {
+            mode:   cfg.beta_mode,
+            fit:    Fit { alpha: 0.0, beta: cfg.beta },
+            kalman: Kalman::new(cfg.kalman, cfg.beta),
+        }
+    }
+
+    pub fn fit(&self) -> Fit { self.fit }
+    pub fn mode(&self) -> BetaMode { self.mode }
+
+    /// Take in the newest log prices (`la`, `lb`) and the window of
+    /// (log P_A, log P_B) ending with them; returns the updated fit.
+    pub fn update(&mut self, la: f64, lb: f64, window: &RollingCov) -> Fit {
+        match self.mode {
+            BetaMode::Fixed => {}
+            BetaMode::RollingOls => {
+                if let Some(fit) = ols(window) {
+                    self.fit = fit;
+                }
+            }
+            BetaMode::Kalman => self.fit = self.kalman.update(la, lb),
+        }
+        self.fit
+    }
+}
+
+/// Least-squares fit of series 0 on series 1 of the window; `None` while
+/// the latter has no variance.
+fn ols(w: &RollingCov) -> Option<Fit> {
+    let var_b = w.var(1);
+    if w.is_empty() || var_b < 1e-18 {
+        return None;
+    }
+    let beta = w.cov(0, 1) / var_b;
+    Some(Fit { alpha: w.mean(0) - beta * w.mean(1), beta })
+}
+
+/// Two-state Kalman filter on x = (α, β) with observation
+/// `la = α + β·lb + ε`.
+struct Kalman {
+    /// state noise per step, added to both diagonal terms of `p`
+    q:      f64,
+    r:      f64,
+    x:      [f64; 2],
+    p:      [[f64; 2]; 2],
+    /// β to start from; α is set from the first observation
+    beta0:  f64,
+    primed: bool,
+}
+
+impl Kalman {
+    fn new(cfg: KalmanCfg, beta0: f64) -> Self {
+        Self {
+            q: cfg.delta / (1.0 - cfg.delta),
+            r: cfg.obs_var,
+            x: [0.0, beta0],
+            p: [[0.0; 2]; 2],
+            beta0,
+            primed: false,
+        }
+    }
+
+    fn update(&mut self, la: f64, lb: f64) -> Fit {
+        if !self.primed {
+            self.x = [la - self.beta0 * lb, self.beta0];
+            self.primed = true;
+        }
+        // predict: the state is a random walk
+        self.p[0][0] += self.q;
+        self.p[1][1] += self.q;
+
+        // correct with h = (1, lb)
+        let h = [1.0, lb];
+        let ph = [
+            self.p[0][0] * h[0] + self.p[0][1] * h[1],
+            self.p[1][0] * h[0] + self.p[1][1] * h[1],
+        ];
+        let s = h[0] * ph[0] + h[1] * ph[1] + self.r;
+        let k = [ph[0] / s, ph[1] / s];
+        let err = la - (self.x[0] + self.x[1] * lb);
+        self.x[0] += k[0] * err;
+        self.x[1] += k[1] * err;
+        let p = self.p;
+        for i in 0..2 {
+            for j in 0..2 {
+                self.p[i][j] = p[i][j] - k[i] * ph[j];
+            }
+        }
+        Fit { alpha: self.x[0], beta: self.x[1] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rand_distr::StandardNormal;
+
+    /// `n` samples of (log P_A, log P_B) with log P_B a random walk and
+    /// log P_A = α + β(t)·log P_B + ε.
+    fn pair(n: usize, alpha: f64, beta: impl Fn(usize) -> f64, seed: u64) -> Vec<(f64, f64)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut lb = 4.6;
+        (0..n).map(|t| {
+            lb += 0.01 * rng.sample::<f64, _>(StandardNormal);
+            let eps = 1e-3 * rng.sample::<f64, _>(StandardNormal);
+            (alpha + beta(t) * lb + eps, lb)
+        }).collect()
+    }
+
+    #[test]
+    fn rolling_ols_recovers_a_constant_beta() {
+        let mut w = RollingCov::new(2, 500);
+        for (la, lb) in pair(2_000, 0.3, |_| 1.5, 1) {
+            w.push(&[la, lb]);
+        }
+        let fit = ols(&w).unwrap();
+        assert!((fit.beta - 1.5).abs() < 0.01, "beta {}", fit.beta);
+        assert!((fit.alpha - 0.3).abs() < 0.05, "alpha {}", fit.alpha);
+    }
+
+    #[test]
+    fn ols_needs_variance_in_the_hedge_leg() {
+        let mut w = RollingCov::new(2, 10);
+        for k in 0..10 {
+            w.push(&[k as f64, 4.6]);
+        }
+        assert_eq!(ols(&w), None);
+    }
+
+    #[test]
+    fn kalman_recovers_a_constant_beta() {
+        let mut kf = Kalman::new(KalmanCfg::default(), 1.0);
+        let mut fit = Fit { alpha: 0.0, beta: 1.0 };
+        for (la, lb) in pair(20_000, 0.3, |_| 1.5, 2) {
+            fit = kf.update(la, lb);
+        }
+        assert!((fit.beta - 1.5).abs() < 0.02, "beta {}", fit.beta);
+    }
+
+    #[test]
+    fn kalman_follows_a_step_in_beta() {
+        let cfg = KalmanCfg { delta: 1e-4, obs_var: 1e-6 };
+        let mut kf = Kalman::new(cfg, 1.5);
+        let data = pair(6_000, 0.3, |t| if t < 3_000 { 1.5 } else { 0.8 }, 3);
+        let mut fit = Fit { alpha: 0.0, beta: 1.5 };
+        for (t, &(la, lb)) in data.iter().enumerate() {
+            fit = kf.update(la, lb);
+            if t == 2_999 {
+                assert!((fit.beta - 1.5).abs() < 0.02, "beta before the step {}", fit.beta);
+            }
+        }
+        assert!((fit.beta - 0.8).abs() < 0.02, "beta after the step {}", fit.beta);
+    }
+}
@@ -0,0 +1,36 @@
+//! Rank every pair of `[select] universe` by cointegration on historical
+//! data and write a config that trades the best one.
+//!
+//! `cargo run -p hft-statarb --bin hft-statarb-select`
+
+use anyhow::{Context, Result};
+use hft_core::history;
+use hft_statarb::{config::StratCfg, select};
+use std::time::Instant;
+use tracing::info;
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let cfg = StratCfg::load()?;
+    let sel = cfg.select.context("no [select] table in Config.toml")?;
+
+    let syms: Vec<&str> = sel.universe.iter().map(String::as_str).collect();
+    let md = history::load(&sel.data, &syms, Instant::now())?;
+    let ranked = select::rank(&sel, &md);
+
+    info!("PAIR {:<10} {:<10} {:>9} {:>9} {:>8} {:>11} {:>8}",
+          "sym_a", "sym_b", "beta", "alpha", "adf_t", "half_life", "lookback");
+    for c in &ranked {
+        info!("PAIR {:<10} {:<10} {:>9.4} {:>9.4} {:>8.3} {:>10.2}s {:>8} {}",
+              c.sym_a, c.sym_b, c.beta, c.alpha, c.adf_t, c.half_life_secs, c.lookback,
+              if c.selected { "✓" } else { "" });
+    }
+
+    let best = ranked.iter().find(|c| c.selected)
+        .context("no pair passes the cointegration screen")?;
+    let base = std::fs::read_to_string("Config.toml")?;
+    std::fs::write(&sel.out, select::config_for(&base, best))?;
+    info!("wrote {} trading {} / {} (beta={:.4}, lookback={})",
+          sel.out, best.sym_a, best.sym_b, best.beta, best.lookback);
+    Ok(())
+}
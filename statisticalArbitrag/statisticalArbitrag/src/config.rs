@@ -1,6 +1,7 @@
 //! Loads strategy parameters from `Config.toml` or `STB_*` env vars.
 
 use anyhow::Result;
+use hft_core::FeeSchedule;
 use figment::providers::{Env, Format, Toml};
 use serde::Deserialize;
 
@@ -15,8 +16,15 @@ pub struct StratCfg {
     /// Rolling-window length in ticks.
     pub lookback: usize,
 
-    /// β coefficient in   spread = log P_A − β·log P_B
+    /// β coefficient in   spread = log P_A − β·log P_B; the starting
+    /// value when β is estimated.
     pub beta: f64,
+    /// How β is kept up to date: fixed, rolling OLS or Kalman filter.
+    #[serde(default)]
+    pub beta_mode: BetaMode,
+    /// Noise settings of the Kalman-filter estimator.
+    #[serde(default)]
+    pub kalman: KalmanCfg,
 
     /// Z-score at which to open a spread trade.
     pub entry_z: f64,
@@ -26,18 +34,284 @@ pub struct StratCfg {
     /// Contract size per leg.
     pub size: f64,
 
-    /// Hard cap on absolute position per leg.
+    /// Hard cap on absolute position per leg, and on the position of each
+    /// symbol netted over every pair and basket.
     pub pos_limit: f64,
 
+    /// Further pairs traded alongside `sym_a` / `sym_b`.
+    #[serde(default)]
+    pub pairs: Vec<PairCfg>,
+    /// N-leg baskets traded on their eigenportfolio, see
+    /// [`basket`](crate::basket).
+    #[serde(default)]
+    pub baskets: Vec<BasketCfg>,
+    /// Cap on the gross exposure Σ |position|·price over all symbols.
+    pub gross_limit: Option<f64>,
+    /// Cap on the net exposure |Σ position·price| over all symbols.
+    pub net_limit: Option<f64>,
+
     /// Simulator tick interval in milliseconds.
     pub tick_ms: u64,
+    /// Return model of the simulator.
+    #[serde(default)]
+    pub sim: SimCfg,
+
+    /// Venue fee schedule: maker / taker bps and per-share fees, optionally
+    /// tiered by traded notional. Charged on every fill.
+    #[serde(default)]
+    pub fees: FeeSchedule,
+
+    /// Simulator RNG seed; the same seed replays the same session.
+    pub seed: u64,
+
+    /// Run on virtual time as fast as the CPU allows instead of real time.
+    #[serde(default)]
+    pub backtest: bool,
+    /// End the session after this many ticks (runs forever if unset).
+    pub max_ticks: Option<u64>,
+
+    /// Write every market-data event and execution to this JSONL file.
+    pub record: Option<String>,
+    /// Replay this recording instead of running the simulator.
+    pub replay: Option<String>,
+    /// Run on historical CSV/Parquet data for both legs instead of the simulator.
+    pub data: Option<String>,
+
+    /// Sharpe/Sortino bucket of the end-of-run report, in ms of session time.
+    pub report_bucket_ms: u64,
+    /// Also write the end-of-run report to this JSON file.
+    pub report_json: Option<String>,
+
+    /// Pair selection run by `hft-statarb-select`.
+    pub select: Option<SelectCfg>,
+}
+
+/// Price process of the simulator, see [`exchange`](crate::exchange): log
+/// prices take correlated Gaussian steps every tick, and a cointegrated
+/// symbol is instead pinned to its partner through a mean-reverting spread.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SimCfg {
+    /// Standard deviation of each symbol's log return per tick.
+    #[serde(default = "default_vol")]
+    pub vol: f64,
+    /// Correlation between the returns of any two symbols.
+    #[serde(default = "default_corr")]
+    pub corr: f64,
+    /// Full per-tick covariance of log returns, symbols in universe order;
+    /// replaces `vol` and `corr`.
+    pub cov: Option<Vec<Vec<f64>>>,
+    /// Mean log return per tick.
+    #[serde(default)]
+    pub drift: f64,
+    /// Cointegrated pairs with a known hedge ratio and half-life.
+    #[serde(default)]
+    pub coint: Vec<CointCfg>,
+}
+
+impl Default for SimCfg {
+    fn default() -> Self {
+        Self { vol: default_vol(), corr: default_corr(), cov: None, drift: 0.0, coint: Vec::new() }
+    }
+}
+
+fn default_vol() -> f64 { 6e-4 }
+fn default_corr() -> f64 { 0.9 }
+
+impl SimCfg {
+    /// Per-tick covariance of log returns for `n` symbols.
+    pub fn cov_matrix(&self, n: usize) -> Vec<Vec<f64>> {
+        if let Some(cov) = &self.cov {
+            return cov.clone();
+        }
+        let v = self.vol * self.vol;
+        (0..n).map(|i| (0..n).map(|j| if i == j { v } else { self.corr * v }).collect()).collect()
+    }
+}
+
+/// `log P_A = α + β·log P_B + s`, with the spread `s` an Ornstein–Uhlenbeck
+/// process around zero; α is set so both legs start at their start price.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CointCfg {
+    pub sym_a: String,
+    pub sym_b: String,
+    pub beta:  f64,
+    /// Time for a deviation of the spread to halve, in seconds.
+    pub half_life_secs: f64,
+    /// Stationary standard deviation of the spread, in log-price units.
+    pub spread_std: f64,
 }
 
+/// One more pair; anything left out is taken from the top level.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PairCfg {
+    pub sym_a:     String,
+    pub sym_b:     String,
+    pub lookback:  Option<usize>,
+    pub beta:      Option<f64>,
+    pub beta_mode: Option<BetaMode>,
+    pub entry_z:   Option<f64>,
+    pub exit_z:    Option<f64>,
+    pub size:      Option<f64>,
+}
+
+/// A basket of several symbols; anything left out is taken from the top
+/// level.
+#[derive(Debug, Deserialize, Clone)]
+pub struct BasketCfg {
+    /// Legs of the basket; the first one is traded in `size` units and the
+    /// others in proportion to their weight.
+    pub symbols:  Vec<String>,
+    pub lookback: Option<usize>,
+    pub entry_z:  Option<f64>,
+    pub exit_z:   Option<f64>,
+    pub size:     Option<f64>,
+}
+
+/// Estimator of the hedge ratio β, see [`beta`](crate::beta).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BetaMode {
+    /// `beta` as configured.
+    #[default]
+    Fixed,
+    /// Least squares of log P_A on log P_B over the lookback window.
+    RollingOls,
+    /// Kalman filter on (α, β), updated every tick.
+    Kalman,
+}
+
+/// Kalman-filter noise: how fast (α, β) may drift against how noisy a
+/// single observation of the spread is.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct KalmanCfg {
+    /// State noise δ; each step (α, β) drifts with variance δ/(1−δ).
+    pub delta: f64,
+    /// Variance of the observation noise ε, in log-price units².
+    pub obs_var: f64,
+}
+
+impl Default for KalmanCfg {
+    fn default() -> Self {
+        Self { delta: 1e-5, obs_var: 1e-6 }
+    }
+}
+
+/// Cointegration screen over a universe, see [`select`](crate::select).
+#[derive(Debug, Deserialize, Clone)]
+pub struct SelectCfg {
+    /// Symbols to pair up.
+    pub universe: Vec<String>,
+    /// Historical CSV/Parquet prices of the universe.
+    pub data: String,
+    /// Lagged differences in the ADF regression.
+    #[serde(default = "default_adf_lags")]
+    pub adf_lags: usize,
+    /// Largest ADF statistic that counts as cointegrated (−3.34 ≈ 5 %).
+    #[serde(default = "default_max_adf_t")]
+    pub max_adf_t: f64,
+    /// Half-life bounds of the spread, in seconds.
+    #[serde(default)]
+    pub min_half_life_secs: f64,
+    #[serde(default = "default_max_half_life_secs")]
+    pub max_half_life_secs: f64,
+    /// Config written with the chosen pair filled in.
+    pub out: String,
+}
+
+fn default_adf_lags() -> usize { 1 }
+fn default_max_adf_t() -> f64 { -3.34 }
+fn default_max_half_life_secs() -> f64 { f64::INFINITY }
+
 impl StratCfg {
     /// Read `Config.toml`, then override with `STB_*` environment variables.
     pub fn load() -> Result<Self> {
-        Ok(figment::Figment::from(Toml::file("Config.toml"))
-            .merge(Env::prefixed("STB_"))
-            .extract()?)
+        Self::extract(figment::Figment::from(Toml::file("Config.toml")).merge(Env::prefixed("STB_")))
+    }
+
+    /// Deserialize and validate the config `figment` provides.
+    pub fn extract(figment: figment::Figment) -> Result<Self> {
+        let cfg: Self = figment.extract()?;
+        anyhow::ensure!(cfg.lookback >= 2, "lookback must be at least 2 ticks");
+        for p in cfg.pairs() {
+            anyhow::ensure!(p.sym_a != p.sym_b, "pair {} / {} trades a symbol against itself", p.sym_a, p.sym_b);
+            anyhow::ensure!(p.lookback >= 2, "pair {} / {}: lookback must be at least 2 ticks", p.sym_a, p.sym_b);
+        }
+        for b in &cfg.baskets {
+            let mut syms = b.symbols.clone();
+            syms.sort();
+            syms.dedup();
+            anyhow::ensure!(syms.len() == b.symbols.len() && syms.len() >= 2,
+                            "basket {:?} needs at least two distinct symbols", b.symbols);
+            anyhow::ensure!(b.lookback.unwrap_or(cfg.lookback) > b.symbols.len(),
+                            "basket {:?}: lookback must exceed the number of legs", b.symbols);
+        }
+        anyhow::ensure!(cfg.gross_limit.is_none_or(|l| l > 0.0) && cfg.net_limit.is_none_or(|l| l > 0.0),
+                        "gross_limit and net_limit must be positive");
+        anyhow::ensure!(cfg.report_bucket_ms > 0, "report_bucket_ms must be positive");
+        cfg.check_sim()?;
+        if let Some(s) = &cfg.select {
+            anyhow::ensure!(s.universe.len() >= 2, "select.universe needs at least two symbols");
+        }
+        anyhow::ensure!(0.0 < cfg.kalman.delta && cfg.kalman.delta < 1.0 && cfg.kalman.obs_var > 0.0,
+                        "kalman needs 0 < delta < 1 and obs_var > 0");
+        Ok(cfg)
+    }
+
+    fn check_sim(&self) -> Result<()> {
+        let universe = self.universe();
+        let n = universe.len();
+        if let Some(cov) = &self.sim.cov {
+            anyhow::ensure!(cov.len() == n && cov.iter().all(|r| r.len() == n),
+                            "sim.cov must be {n}×{n}, one row per symbol of {universe:?}");
+            let symmetric = (0..n).all(|i| (0..i).all(|j| (cov[i][j] - cov[j][i]).abs() <= 1e-15));
+            anyhow::ensure!(symmetric, "sim.cov must be symmetric");
+        }
+        anyhow::ensure!(self.sim.vol >= 0.0, "sim.vol must not be negative");
+        anyhow::ensure!(crate::exchange::cholesky(&self.sim.cov_matrix(n)).is_some(),
+                        "sim covariance must be positive semi-definite");
+        for (k, c) in self.sim.coint.iter().enumerate() {
+            let known = |s: &String| universe.contains(s);
+            anyhow::ensure!(known(&c.sym_a) && known(&c.sym_b) && c.sym_a != c.sym_b,
+                            "sim.coint {} / {} needs two different traded symbols", c.sym_a, c.sym_b);
+            anyhow::ensure!(c.half_life_secs > 0.0 && c.spread_std >= 0.0,
+                            "sim.coint {} / {} needs half_life_secs > 0 and spread_std >= 0", c.sym_a, c.sym_b);
+            anyhow::ensure!(self.sim.coint[..k].iter().all(|o| o.sym_a != c.sym_a),
+                            "sim.coint pins {} more than once", c.sym_a);
+        }
+        Ok(())
+    }
+
+    /// One config per pair, `sym_a` / `sym_b` first, with the per-pair
+    /// overrides applied.
+    pub fn pairs(&self) -> Vec<StratCfg> {
+        let first = StratCfg { pairs: Vec::new(), baskets: Vec::new(), ..self.clone() };
+        let mut pairs = vec![first.clone()];
+        for p in &self.pairs {
+            pairs.push(StratCfg {
+                sym_a:     p.sym_a.clone(),
+                sym_b:     p.sym_b.clone(),
+                lookback:  p.lookback.unwrap_or(self.lookback),
+                beta:      p.beta.unwrap_or(self.beta),
+                beta_mode: p.beta_mode.unwrap_or(self.beta_mode),
+                entry_z:   p.entry_z.unwrap_or(self.entry_z),
+                exit_z:    p.exit_z.unwrap_or(self.exit_z),
+                size:      p.size.unwrap_or(self.size),
+                ..first.clone()
+            });
+        }
+        pairs
+    }
+
+    /// Every symbol traded by a pair or basket, in order of first mention.
+    pub fn universe(&self) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        let legs = self.pairs().into_iter().flat_map(|p| [p.sym_a, p.sym_b])
+            .chain(self.baskets.iter().flat_map(|b| b.symbols.clone()));
+        for s in legs {
+            if !out.contains(&s) {
+                out.push(s);
+            }
+        }
+        out
     }
 }
@@ -1,64 +1,346 @@
-use crate::models::*;
-use rand::Rng;
+//! Multi-asset price simulator. Every tick the log prices take a step
+//!
+//! ```text
+//! Δ log P = drift + L·ε        ε ~ N(0, I),  L·Lᵀ = per-tick covariance
+//! ```
+//!
+//! and every cointegrated symbol is then re-pinned to its partner,
+//!
+//! ```text
+//! log P_A = α + β·log P_B + s        s ← φ·s + σ_s·√(1−φ²)·η,  φ = 2^(−Δt / half-life)
+//! ```
+//!
+//! so the spread is an Ornstein–Uhlenbeck process with a known β,
+//! half-life and stationary deviation σ_s: ground truth for the estimators.
+
+use crate::config::SimCfg;
+use hft_core::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tracing::info;
 
-/// Generates price ticks for two symbols whose returns share ρ ≈ 0.9.
+/// Generates correlated price ticks for several symbols.
 pub struct Exchange {
-    md_tx: mpsc::Sender<Tick>,
-    fill_tx: mpsc::Sender<Fill>,
-    px_a: f64,
-    px_b: f64,
-    symbol_a: String,
-    symbol_b: String,
+    md_tx: mpsc::Sender<MarketData>,
+    er_tx: mpsc::Sender<ExecReport>,
+    symbols: Vec<String>,
+    px: Vec<f64>,
+    log_px: Vec<f64>,
+    /// lower Cholesky factor of the per-tick return covariance
+    chol: Vec<Vec<f64>>,
+    drift: f64,
+    coint: Vec<Coint>,
+    /// sole source of randomness, so a seed replays the same session
+    rng: StdRng,
+    /// session time of the current step, stamped on fills
+    now: Instant,
+    fees: FeeMeter,
 }
 
 impl Exchange {
-    pub fn new(md_tx: mpsc::Sender<Tick>,
-               fill_tx: mpsc::Sender<Fill>,
-               symbol_a: &str,
-               symbol_b: &str,
-               start_px: f64) -> Self
+    pub fn new(md_tx: mpsc::Sender<MarketData>,
+               er_tx: mpsc::Sender<ExecReport>,
+               symbols: &[String],
+               start_px: f64,
+               seed: u64) -> Self
     {
+        let px: Vec<f64> = (0..symbols.len()).map(|k| start_px * 0.98f64.powi(k as i32)).collect();
         Self {
             md_tx,
-            fill_tx,
-            px_a: start_px,
-            px_b: start_px * 0.98,
-            symbol_a: symbol_a.to_string(),
-            symbol_b: symbol_b.to_string(),
+            er_tx,
+            symbols: symbols.to_vec(),
+            log_px: px.iter().map(|p| p.ln()).collect(),
+            px,
+            chol: Vec::new(),
+            drift: 0.0,
+            coint: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            now: Instant::now(),
+            fees: FeeMeter::default(),
         }
+        .market(&SimCfg::default(), 1000)
+    }
+
+    /// Drive prices by `sim`, stepping every `tick_ms`. Expects a config
+    /// that passed [`StratCfg::load`](crate::config::StratCfg::load).
+    pub fn market(mut self, sim: &SimCfg, tick_ms: u64) -> Self {
+        let n = self.symbols.len();
+        self.chol = cholesky(&sim.cov_matrix(n)).expect("covariance checked on load");
+        self.drift = sim.drift;
+        let slot = |s: &str| self.symbols.iter().position(|x| x == s).expect("coint symbol checked on load");
+        self.coint = sim.coint.iter().map(|c| {
+            let (a, b) = (slot(&c.sym_a), slot(&c.sym_b));
+            let phi = 0.5f64.powf(tick_ms as f64 / 1000.0 / c.half_life_secs);
+            Coint {
+                a,
+                b,
+                alpha: self.log_px[a] - c.beta * self.log_px[b],
+                beta: c.beta,
+                phi,
+                noise: c.spread_std * (1.0 - phi * phi).sqrt(),
+                spread: 0.0,
+            }
+        }).collect();
+        self
     }
 
-    pub async fn run(mut self, tick_ms: u64, mut od_rx: mpsc::Receiver<Order>) {
-        let mut intv = tokio::time::interval(std::time::Duration::from_millis(tick_ms));
-        let rho = 0.9;
+    /// Charge fills with `fees` (none by default).
+    pub fn fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = FeeMeter::new(fees);
+        self
+    }
+
+    pub async fn run(mut self, mut clock: Clock, mut od_rx: mpsc::Receiver<OrderReq>,
+                     mut done_rx: mpsc::Receiver<()>) {
+        for c in &self.coint {
+            info!("SIM {} = {:.4} + {:.4}·{} + OU spread, phi={:.4} per tick",
+                  self.symbols[c.a], c.alpha, c.beta, self.symbols[c.b], c.phi);
+        }
 
         loop {
             tokio::select! {
-                _ = intv.tick() => {
-                    // correlated normal shocks
-                    let n1 = rand::random::<f64>();
-                    let n2 = rand::random::<f64>();
-                    let z1 = (n1 - 0.5) * 0.002;                 // small drift
-                    let z2 = rho*z1 + (1.0-rho.powi(2)).sqrt()*((n2-0.5)*0.002);
-
-                    self.px_a *= (1.0 + z1);
-                    self.px_b *= (1.0 + z2);
-
-                    let now = std::time::Instant::now();
-                    let _ = self.md_tx.send(Tick{symbol:self.symbol_a.clone(),px:self.px_a,ts:now}).await;
-                    let _ = self.md_tx.send(Tick{symbol:self.symbol_b.clone(),px:self.px_b,ts:now}).await;
+                // requests already queued are handled before the next tick
+                biased;
+
+                Some(req) = od_rx.recv() => {
+                    for er in self.on_request(req) {
+                        let _ = self.er_tx.send(er).await;
+                    }
                 }
 
-                Some(ord) = od_rx.recv() => {
-                    let book_px = if ord.symbol == self.symbol_a { self.px_a } else { self.px_b };
-                    // cross at mid immediately
-                    let fill = Fill { symbol: ord.symbol.clone(), px: book_px, qty: ord.qty, side: ord.side };
-                    let _ = self.fill_tx.send(fill).await;
+                now = clock.tick() => {
+                    // ticks carry session time, not wall time
+                    let Some(now) = now else { break };
+                    self.now = now;
+
+                    self.step();
+
+                    for (sym, &px) in self.symbols.iter().zip(&self.px) {
+                        let _ = self.md_tx.send(MarketData::Tick(Tick::at(sym, px, now))).await;
+                    }
+
+                    // wait for the strategy to react before stepping again
+                    if !self.settle(self.px.len(), &mut od_rx, &mut done_rx).await {
+                        break;
+                    }
                 }
 
                 else => break,
             }
         }
+        info!("session over after {} ticks", clock.ticks());
+    }
+
+    /// Move every log price one tick, then re-pin the cointegrated ones.
+    fn step(&mut self) {
+        let n = self.symbols.len();
+        let eps: Vec<f64> = (0..n).map(|_| self.rng.sample(StandardNormal)).collect();
+        for i in 0..n {
+            let shock: f64 = (0..=i).map(|j| self.chol[i][j] * eps[j]).sum();
+            self.log_px[i] += self.drift + shock;
+        }
+        for c in &mut self.coint {
+            let eta: f64 = self.rng.sample(StandardNormal);
+            c.spread = c.phi * c.spread + c.noise * eta;
+            self.log_px[c.a] = c.alpha + c.beta * self.log_px[c.b] + c.spread;
+        }
+        for (px, lp) in self.px.iter_mut().zip(&self.log_px) {
+            *px = lp.exp();
+        }
+    }
+
+    /// Lockstep with the strategy: wait until it has reacted to the `n`
+    /// ticks just published, then take in everything it sent before
+    /// stepping again. This keeps a seeded run identical however fast
+    /// either side is.
+    async fn settle(&mut self,
+                    n: usize,
+                    od_rx: &mut mpsc::Receiver<OrderReq>,
+                    done_rx: &mut mpsc::Receiver<()>) -> bool
+    {
+        for _ in 0..n {
+            if done_rx.recv().await.is_none() {
+                return false;
+            }
+        }
+        while let Ok(req) = od_rx.try_recv() {
+            for er in self.on_request(req) {
+                let _ = self.er_tx.send(er).await;
+            }
+        }
+        true
+    }
+
+    /// Orders cross at mid immediately, so there is never anything left
+    /// resting to cancel or amend.
+    fn on_request(&mut self, req: OrderReq) -> Vec<ExecReport> {
+        match req {
+            OrderReq::New(ord) => {
+                let Some(k) = self.symbols.iter().position(|s| *s == ord.symbol) else {
+                    return vec![ExecReport::Reject { id: ord.id, reason: "unknown symbol".into() }];
+                };
+                let book_px = self.px[k];
+                vec![
+                    ExecReport::NewOrderAck { id: ord.id },
+                    ExecReport::Fill(self.fees.charge(Fill {
+                        id: ord.id, symbol: ord.symbol, px: book_px,
+                        qty: ord.qty, side: ord.side, leaves: 0.0,
+                        liquidity: Liquidity::Taker, fee: 0.0, ts: self.now,
+                    })),
+                ]
+            }
+            OrderReq::Cancel { id } | OrderReq::Replace { id, .. } => {
+                vec![ExecReport::Reject { id, reason: "unknown or closed order".into() }]
+            }
+        }
+    }
+}
+
+/// A symbol pinned to its partner by an OU spread.
+struct Coint {
+    a:      usize,
+    b:      usize,
+    alpha:  f64,
+    beta:   f64,
+    /// per-tick persistence of the spread and the noise added each tick
+    phi:    f64,
+    noise:  f64,
+    spread: f64,
+}
+
+/// Lower-triangular `L` with `L·Lᵀ = m` for a positive semi-definite `m`
+/// (rows and columns of zero variance get zeros); `None` otherwise.
+pub fn cholesky(m: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = m.len();
+    let scale = (0..n).map(|i| m[i][i].abs()).fold(0.0, f64::max);
+    let tol = scale * 1e-9;
+    let mut l = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        let d = m[j][j] - (0..j).map(|k| l[j][k] * l[j][k]).sum::<f64>();
+        if d < -tol {
+            return None;
+        }
+        if d <= tol {
+            // degenerate direction: what is left of the column must vanish too
+            for i in j + 1..n {
+                let r = m[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+                if r.abs() > tol {
+                    return None;
+                }
+            }
+            continue;
+        }
+        l[j][j] = d.sqrt();
+        for i in j + 1..n {
+            let r = m[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            l[i][j] = r / l[j][j];
+        }
+    }
+    Some(l)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CointCfg, StratCfg};
+    use figment::providers::{Format, Toml};
+
+    fn exchange(symbols: &[&str], sim: &SimCfg, tick_ms: u64) -> Exchange {
+        let (md_tx, _) = mpsc::channel(1);
+        let (er_tx, _) = mpsc::channel(1);
+        let symbols: Vec<String> = symbols.iter().map(|s| s.to_string()).collect();
+        Exchange::new(md_tx, er_tx, &symbols, 100.0, 42).market(sim, tick_ms)
+    }
+
+    fn times_transpose(l: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let n = l.len();
+        (0..n).map(|i| (0..n).map(|j| (0..n).map(|k| l[i][k] * l[j][k]).sum()).collect()).collect()
+    }
+
+    #[test]
+    fn cholesky_reproduces_the_matrix() {
+        let pd = vec![
+            vec![4.0e-6, 1.2e-6, -0.8e-6],
+            vec![1.2e-6, 9.0e-6, 2.1e-6],
+            vec![-0.8e-6, 2.1e-6, 1.0e-6],
+        ];
+        // perfectly correlated legs: semi-definite, still accepted
+        let psd = vec![vec![1.0, 1.0, 0.5], vec![1.0, 1.0, 0.5], vec![0.5, 0.5, 1.0]];
+        for m in [pd, psd] {
+            let l = cholesky(&m).unwrap();
+            let back = times_transpose(&l);
+            for i in 0..m.len() {
+                assert!((0..i).all(|j| l[j][i] == 0.0), "L is lower-triangular");
+                for j in 0..m.len() {
+                    assert!((back[i][j] - m[i][j]).abs() <= 1e-12 * m[i][i].max(m[j][j]), "{back:?} vs {m:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn indefinite_covariance_is_rejected_on_load() {
+        assert!(cholesky(&[vec![1.0, 2.0], vec![2.0, 1.0]]).is_none());
+
+        let base = include_str!("../Config.toml");
+        for sim in ["[sim]\ncov = [[1e-6, 2e-6], [2e-6, 1e-6]]", "[sim]\ncorr = 1.5"] {
+            let fig = figment::Figment::from(Toml::string(base)).merge(Toml::string(sim));
+            let err = StratCfg::extract(fig).unwrap_err().to_string();
+            assert!(err.contains("positive semi-definite"), "{sim}: {err}");
+        }
+    }
+
+    #[test]
+    fn returns_have_the_configured_vol_and_correlation() {
+        let sim = SimCfg { vol: 1e-3, corr: 0.6, ..SimCfg::default() };
+        let mut ex = exchange(&["A", "B", "C"], &sim, 50);
+        let n = 20_000;
+        let mut rets = vec![Vec::new(); 3];
+        for _ in 0..n {
+            let before = ex.log_px.clone();
+            ex.step();
+            for (k, r) in rets.iter_mut().enumerate() {
+                r.push(ex.log_px[k] - before[k]);
+            }
+            assert!(ex.px.iter().all(|p| p.is_finite() && *p > 0.0));
+        }
+        let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+        let cov = |x: &[f64], y: &[f64]| {
+            let (mx, my) = (mean(x), mean(y));
+            x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum::<f64>() / x.len() as f64
+        };
+        for i in 0..3 {
+            let vol = cov(&rets[i], &rets[i]).sqrt();
+            assert!((vol - 1e-3).abs() < 3e-5, "vol {i} = {vol}");
+            for j in 0..i {
+                let corr = cov(&rets[i], &rets[j]) / (cov(&rets[i], &rets[i]) * cov(&rets[j], &rets[j])).sqrt();
+                assert!((corr - 0.6).abs() < 0.03, "corr {i},{j} = {corr}");
+            }
+        }
+    }
+
+    #[test]
+    fn cointegrated_spread_reverts_at_the_configured_half_life() {
+        let sim = SimCfg {
+            coint: vec![CointCfg {
+                sym_a: "A".into(), sym_b: "B".into(), beta: 1.2, half_life_secs: 2.0, spread_std: 0.002,
+            }],
+            ..SimCfg::default()
+        };
+        // 100 ms ticks: a half-life of 20 ticks
+        let mut ex = exchange(&["A", "B"], &sim, 100);
+        let alpha = ex.coint[0].alpha;
+        let spread: Vec<f64> = (0..50_000).map(|_| {
+            ex.step();
+            ex.log_px[0] - alpha - 1.2 * ex.log_px[1]
+        }).collect();
+
+        let half_life = crate::select::half_life(&spread).unwrap();
+        assert!((half_life - 20.0).abs() < 2.0, "half-life {half_life} ticks");
+        let std = (spread.iter().map(|s| s * s).sum::<f64>() / spread.len() as f64).sqrt();
+        assert!((std - 0.002).abs() < 2e-4, "spread std {std}");
     }
 }
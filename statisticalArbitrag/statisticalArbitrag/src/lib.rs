@@ -0,0 +1,11 @@
+//! Rolling z-score pairs and basket traders and their correlated
+//! multi-asset venue.
+
+pub mod basket;
+pub mod beta;
+pub mod config;
+pub mod exchange;
+pub mod portfolio;
+pub mod risk;
+pub mod select;
+pub mod strategy;
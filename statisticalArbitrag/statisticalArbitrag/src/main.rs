@@ -1,15 +1,9 @@
-mod config;
-mod models;
-mod exchange;
-mod strategy;
-mod risk;
-
 use anyhow::Result;
-use tracing::info;
-use tokio::sync::mpsc;
 
-use crate::config::StratCfg;
-use crate::models::*;
+use hft_core::*;
+use hft_statarb::{config::StratCfg, exchange, portfolio::Portfolio};
+use std::time::Duration;
+use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -17,60 +11,56 @@ async fn main() -> Result<()> {
     let cfg = StratCfg::load()?;
 
     // ---------- channels ----------
-    let (md_tx, mut md_rx)   = mpsc::channel::<Tick>(2048);
-    let (fill_tx, mut fills) = mpsc::channel::<Fill>(2048);
-    let (od_tx, od_rx)       = mpsc::channel::<Order>(2048);
-
-    // ---------- start simulator ----------
-    tokio::spawn(
-        exchange::Exchange::new(md_tx, fill_tx, &cfg.sym_a, &cfg.sym_b, 100.0)
-            .run(cfg.tick_ms, od_rx),
-    );
-
-    // ---------- strategy + risk ----------
-    let mut strat   = strategy::PairTrader::new(cfg.clone());
-    let     riskmgr = risk::Risk::new(cfg.clone());
-
-    // cache latest prices so we call on_ticks() once per interval
-    let mut last_a = 100.0;
-    let mut last_b = 98.0;
-
-    loop {
-        tokio::select! {
-            /* ---------- market-data ---------- */
-            Some(tick) = md_rx.recv() => {
-                if tick.symbol == cfg.sym_a { last_a = tick.px; }
-                else                        { last_b = tick.px; }
-
-                // call strategy only when we have both legs (B arrives last)
-                if tick.symbol == cfg.sym_b {
-                    let orders = strat.on_ticks(last_a, last_b);
+    let (venue, engine) = link(2048);
 
-                    for o in orders {
-                        let delta = if matches!(o.side, Side::Buy) { o.qty } else { -o.qty };
-                        let pos_after = if o.symbol == cfg.sym_a {
-                            strat.pos_a() + delta
-                        } else {
-                            strat.pos_b() + delta
-                        };
+    // ---------- start simulator (or replay a recording) ----------
+    let clock    = Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks);
+    let origin   = clock.start();
+    let universe = cfg.universe();
+    match (&cfg.replay, &cfg.data) {
+        (Some(path), _) => {
+            let rp = Replay::open(path, venue.md_tx, venue.er_tx)?.fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, Some(path)) => {
+            let syms: Vec<&str> = universe.iter().map(String::as_str).collect();
+            let md = history::load(path, &syms, origin)?;
+            let rp = Replay::new(md, venue.md_tx, venue.er_tx).fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, None) => {
+            tokio::spawn(
+                exchange::Exchange::new(venue.md_tx, venue.er_tx, &universe, 100.0, cfg.seed)
+                    .market(&cfg.sim, cfg.tick_ms)
+                    .fees(cfg.fees.clone())
+                    .run(clock, venue.od_rx, venue.done_rx),
+            );
+        }
+    }
 
-                        if riskmgr.allow(&o, pos_after) {
-                            od_tx.send(o).await?;
-                        }
-                    }
-                }
-            }
+    // ---------- end-of-run report ----------
+    let mut perf = Performance::new("hft-statarb", Duration::from_millis(cfg.report_bucket_ms));
+    if let Some(path) = &cfg.report_json {
+        perf = perf.json(path);
+    }
 
-            /* ---------- fills ---------- */
-            Some(fill) = fills.recv() => {
-                strat.on_fill(&fill);
-                info!("FILL {:?} {:.2} {} @ {:.2}",
-                      fill.side, fill.qty, fill.symbol, fill.px);
-            }
+    // ---------- strategy; the portfolio checks risk a batch at a time ----------
+    let strat = Portfolio::new(&cfg);
 
-            else => break,
-        }
+    let mut engine = Engine::new(strat, engine).report(perf);
+    if let Some(path) = &cfg.record {
+        engine = engine.record(Recorder::create(path, origin)?);
+    }
+    let pf = engine.run().await;
+    for pair in pf.pairs() {
+        let hedge = pair.hedge_ratio();
+        info!("BETA {}/{} {:?} beta={:.4} alpha={:.4}", pair.cfg().sym_a, pair.cfg().sym_b,
+              hedge.mode(), hedge.fit().beta, hedge.fit().alpha);
+    }
+    for basket in pf.baskets() {
+        basket.log_report();
     }
+    pf.log_summary();
 
     Ok(())
 }
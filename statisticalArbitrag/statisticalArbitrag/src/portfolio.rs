@@ -0,0 +1,152 @@
+//! Many pairs and baskets traded from one process: one [`PairTrader`] per
+//! pair and one [`Basket`] per basket behind a single engine, with the
+//! positions netted per symbol across all of them for the risk checks.
+//!
+//! Trader `k` of `n` (pairs first) numbers its orders `k+1, k+1+n, …`, so
+//! every execution report finds its way back to the trader that sent it.
+//! The orders a trader sends for one event go out together or not at all,
+//! so a limit never leaves a spread with one leg.
+
+use crate::basket::Basket;
+use crate::config::StratCfg;
+use crate::risk::Risk;
+use crate::strategy::PairTrader;
+use hft_core::{ExecReport, MarketData, Order, OrderId, OrderIds, OrderReq, Strategy};
+use tracing::{debug, info};
+
+/// Position per symbol netted over every trader, marked at the latest mid.
+#[derive(Debug, Clone)]
+pub struct Netting {
+    symbols: Vec<String>,
+    pos:     Vec<f64>,
+    px:      Vec<f64>,
+}
+
+impl Netting {
+    pub(crate) fn new(symbols: Vec<String>) -> Self {
+        Self { pos: vec![0.0; symbols.len()], px: vec![0.0; symbols.len()], symbols }
+    }
+
+    /// Net position in `symbol`.
+    pub fn pos(&self, symbol: &str) -> f64 {
+        self.slot(symbol).map_or(0.0, |k| self.pos[k])
+    }
+
+    /// Net position in `o`'s symbol if `o` fills completely.
+    pub fn pos_after(&self, o: &Order) -> f64 {
+        self.pos(&o.symbol) + o.side.sign() * o.qty
+    }
+
+    /// Gross Σ |position|·price and net Σ position·price over all symbols.
+    pub fn exposure(&self) -> (f64, f64) {
+        self.pos.iter().zip(&self.px)
+            .fold((0.0, 0.0), |(g, n), (p, px)| (g + (p * px).abs(), n + p * px))
+    }
+
+    /// [`exposure`](Self::exposure) if `o` fills completely at the latest mid.
+    pub fn exposure_after(&self, o: &Order) -> (f64, f64) {
+        let (gross, net) = self.exposure();
+        let Some(k) = self.slot(&o.symbol) else { return (gross, net) };
+        let (before, after) = (self.pos[k] * self.px[k], self.pos_after(o) * self.px[k]);
+        (gross - before.abs() + after.abs(), net - before + after)
+    }
+
+    pub(crate) fn fill(&mut self, symbol: &str, signed_qty: f64) {
+        if let Some(k) = self.slot(symbol) {
+            self.pos[k] += signed_qty;
+        }
+    }
+
+    fn slot(&self, symbol: &str) -> Option<usize> {
+        self.symbols.iter().position(|s| s == symbol)
+    }
+}
+
+pub struct Portfolio {
+    pairs:   Vec<PairTrader>,
+    baskets: Vec<Basket>,
+    netting: Netting,
+    risk:    Risk,
+}
+
+impl Portfolio {
+    pub fn new(cfg: &StratCfg) -> Self {
+        let n = (cfg.pairs().len() + cfg.baskets.len()) as OrderId;
+        let mut first = 1..;
+        let pairs = cfg.pairs().into_iter().zip(&mut first)
+            .map(|(c, k)| PairTrader::new(c).ids(OrderIds::strided(k, n)))
+            .collect();
+        let baskets = cfg.baskets.iter().zip(&mut first)
+            .map(|(b, k)| Basket::new(cfg, b).ids(OrderIds::strided(k, n)))
+            .collect();
+        Self { pairs, baskets, netting: Netting::new(cfg.universe()), risk: Risk::new(cfg.clone()) }
+    }
+
+    pub fn pairs(&self) -> &[PairTrader] { &self.pairs }
+    pub fn baskets(&self) -> &[Basket] { &self.baskets }
+    pub fn netting(&self) -> &Netting { &self.netting }
+
+    /// Net positions and exposure; logged only when running more than one
+    /// pair or basket.
+    pub fn log_summary(&self) {
+        if self.pairs.len() + self.baskets.len() < 2 {
+            return;
+        }
+        let (gross, net) = self.netting.exposure();
+        let pos: Vec<String> = self.netting.symbols.iter().zip(&self.netting.pos)
+            .map(|(s, p)| format!("{s}={p:+.2}"))
+            .collect();
+        info!("PORTFOLIO pairs={} baskets={} gross={:.2} net={:.2} pos {}",
+              self.pairs.len(), self.baskets.len(), gross, net, pos.join(" "));
+    }
+
+    /// The trader that sent order `id`.
+    fn trader(&mut self, id: OrderId) -> &mut dyn Strategy {
+        let n = (self.pairs.len() + self.baskets.len()) as OrderId;
+        let k = (id.saturating_sub(1) % n) as usize;
+        match self.pairs.len() {
+            len if k < len => &mut self.pairs[k],
+            len => &mut self.baskets[k - len],
+        }
+    }
+}
+
+impl Strategy for Portfolio {
+    /// Every trader sees every event and acts on its own symbols only; a
+    /// trader's orders are dropped together if any of them breaks a limit,
+    /// given the orders of the traders before it.
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        if let MarketData::Tick(t) = md {
+            if let Some(k) = self.netting.slot(&t.symbol) {
+                self.netting.px[k] = t.mid();
+            }
+        }
+        let batches: Vec<Vec<OrderReq>> = self.pairs.iter_mut().map(|p| p.on_market_data(md))
+            .chain(self.baskets.iter_mut().map(|b| b.on_market_data(md)))
+            .collect();
+
+        // orders of earlier traders count as filled for the later ones
+        let mut after = self.netting.clone();
+        let mut reqs = Vec::new();
+        for batch in batches {
+            if !self.risk.allow_batch(&after, &batch) {
+                debug!("held back {} orders over the limits", batch.len());
+                continue;
+            }
+            for req in &batch {
+                if let OrderReq::New(o) = req {
+                    after.fill(&o.symbol, o.side.sign() * o.qty);
+                }
+            }
+            reqs.extend(batch);
+        }
+        reqs
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        if let ExecReport::Fill(f) = er {
+            self.netting.fill(&f.symbol, f.side.sign() * f.qty);
+        }
+        self.trader(er.id()).on_execution(er)
+    }
+}
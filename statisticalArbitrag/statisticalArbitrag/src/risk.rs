@@ -1,6 +1,9 @@
-//! Very simple position-limit checker.
+//! Position and exposure limits, on positions netted across every pair and
+//! basket.
 
-use crate::{config::StratCfg, models::*};
+use crate::config::StratCfg;
+use crate::portfolio::Netting;
+use hft_core::{Order, OrderReq};
 
 pub struct Risk {
     cfg: StratCfg,
@@ -11,8 +14,66 @@ impl Risk {
         Self { cfg }
     }
 
-    /// Returns `true` if `pos_after` stays within ±pos_limit.
-    pub fn allow(&self, _ord: &Order, pos_after: f64) -> bool {
-        pos_after.abs() <= self.cfg.pos_limit
+    /// Returns `true` if the net position in `ord`'s symbol stays within
+    /// ±pos_limit or `ord` shrinks it, so a position over the limit can
+    /// always be worked down.
+    pub fn allow(&self, net: &Netting, ord: &Order) -> bool {
+        let (before, after) = (net.pos(&ord.symbol).abs(), net.pos_after(ord).abs());
+        after <= self.cfg.pos_limit || after <= before
+    }
+
+    /// Returns `true` if every order of `batch`, filled one after the
+    /// other, passes [`allow`](Self::allow) and, where it grows them, keeps
+    /// the gross and net exposure within their limits.
+    pub fn allow_batch(&self, net: &Netting, batch: &[OrderReq]) -> bool {
+        let breaches = |limit: Option<f64>, before: f64, after: f64| {
+            after > before && limit.is_some_and(|l| after > l)
+        };
+        let mut net = net.clone();
+        batch.iter().all(|req| {
+            let OrderReq::New(ord) = req else { return true };
+            let (gross, net_now) = net.exposure();
+            let (gross_after, net_after) = net.exposure_after(ord);
+            let ok = self.allow(&net, ord)
+                && !breaches(self.cfg.gross_limit, gross, gross_after)
+                && !breaches(self.cfg.net_limit, net_now.abs(), net_after.abs());
+            net.fill(&ord.symbol, ord.side.sign() * ord.qty);
+            ok
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use figment::providers::{Format, Toml};
+    use hft_core::Side;
+
+    fn risk() -> Risk {
+        let cfg = StratCfg::extract(figment::Figment::from(Toml::string(include_str!("../Config.toml")))).unwrap();
+        assert_eq!(cfg.pos_limit, 10.0);
+        Risk::new(cfg)
+    }
+
+    fn new(id: u64, sym: &str, side: Side, qty: f64) -> OrderReq {
+        OrderReq::New(Order::market(id, sym, side, qty))
+    }
+
+    #[test]
+    fn flatten_passes_over_the_limit_and_growth_does_not() {
+        let risk = risk();
+        let mut net = Netting::new(vec!["SYM_A".into(), "SYM_B".into()]);
+        // another trader's fills left SYM_A over its limit
+        net.fill("SYM_A", 12.0);
+        net.fill("SYM_B", -8.0);
+
+        let flatten = [new(1, "SYM_A", Side::Sell, 12.0), new(2, "SYM_B", Side::Buy, 8.0)];
+        assert!(risk.allow_batch(&net, &flatten));
+        assert!(risk.allow_batch(&net, &[new(3, "SYM_A", Side::Sell, 1.0)]), "still over, but smaller");
+
+        let grow = [new(4, "SYM_B", Side::Sell, 1.0), new(5, "SYM_A", Side::Buy, 1.0)];
+        assert!(!risk.allow_batch(&net, &grow), "the whole batch goes when one leg grows past the limit");
+        assert!(!risk.allow_batch(&net, &[new(6, "SYM_B", Side::Sell, 3.0)]));
+        assert!(risk.allow_batch(&net, &[new(7, "SYM_B", Side::Sell, 2.0)]));
     }
 }
@@ -0,0 +1,326 @@
+//! Pair selection: which two symbols of a universe are worth trading.
+//!
+//! For every pair, on log prices sampled whenever a symbol ticks:
+//!
+//! 1. **Engle–Granger** — least squares `log P_A = α + β·log P_B + e`, in
+//!    both orders;
+//! 2. **ADF** on the residual, `Δe_t = γ·e_{t−1} + Σ φ_i·Δe_{t−i}`: the more
+//!    negative the t-statistic of γ, the stronger the mean reversion
+//!    (Engle–Granger critical values for two series: −3.90 at 1 %, −3.34 at
+//!    5 %, −3.04 at 10 %);
+//! 3. **half-life** of a shock to the spread, from `Δe_t = c + λ·e_{t−1}`:
+//!    `ln 2 / −ln(1 + λ)` samples.
+//!
+//! Pairs are ranked by their ADF statistic; the best one that passes is
+//! written into a copy of the config with `sym_a`, `sym_b`, `beta` and a
+//! `lookback` of three half-lives.
+
+use crate::config::SelectCfg;
+use hft_core::MarketData;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+/// Test results for one ordered pair.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub sym_a:          String,
+    pub sym_b:          String,
+    pub alpha:          f64,
+    pub beta:           f64,
+    /// ADF t-statistic of the Engle–Granger residual
+    pub adf_t:          f64,
+    pub half_life_secs: f64,
+    /// suggested z-score window, in samples
+    pub lookback:       usize,
+    /// passes `max_adf_t` and the half-life bounds
+    pub selected:       bool,
+}
+
+/// Log mids of `universe` on a common clock: one row per event time once
+/// every symbol has ticked, each symbol carrying its latest mid forward.
+/// Also returns the median spacing of the rows in seconds.
+pub fn log_prices(md: &[MarketData], universe: &[String]) -> (Vec<Vec<f64>>, f64) {
+    let mut last: Vec<Option<f64>> = vec![None; universe.len()];
+    let mut rows = Vec::new();
+    let mut stamps: Vec<Instant> = Vec::new();
+    let mut group: Option<Instant> = None;
+    let mut snapshot = |last: &[Option<f64>], ts: Instant| {
+        if let Some(row) = last.iter().copied().collect::<Option<Vec<f64>>>() {
+            rows.push(row);
+            stamps.push(ts);
+        }
+    };
+    for md in md {
+        let MarketData::Tick(t) = md else { continue };
+        let Some(k) = universe.iter().position(|s| *s == t.symbol) else { continue };
+        if let Some(ts) = group.filter(|&ts| ts != t.ts) {
+            snapshot(&last, ts);
+        }
+        group = Some(t.ts);
+        last[k] = Some(t.mid().ln());
+    }
+    if let Some(ts) = group {
+        snapshot(&last, ts);
+    }
+
+    let mut gaps: Vec<f64> = stamps.windows(2)
+        .map(|w| w[1].saturating_duration_since(w[0]).as_secs_f64())
+        .collect();
+    gaps.sort_by(f64::total_cmp);
+    let dt = gaps.get(gaps.len() / 2).copied().unwrap_or(0.0);
+    (rows, dt)
+}
+
+/// Test every pair of `universe` in both orders and keep the better order
+/// of each; best first.
+pub fn rank(cfg: &SelectCfg, md: &[MarketData]) -> Vec<Candidate> {
+    let (rows, dt) = log_prices(md, &cfg.universe);
+    let column = |k: usize| rows.iter().map(|r| r[k]).collect::<Vec<_>>();
+    let series: Vec<Vec<f64>> = (0..cfg.universe.len()).map(column).collect();
+
+    let mut out = Vec::new();
+    for i in 0..series.len() {
+        for j in i + 1..series.len() {
+            let best = [(i, j), (j, i)].into_iter()
+                .filter_map(|(a, b)| test_pair(cfg, &series[a], &series[b], dt)
+                    .map(|c| Candidate { sym_a: cfg.universe[a].clone(), sym_b: cfg.universe[b].clone(), ..c }))
+                .min_by(|x, y| x.adf_t.total_cmp(&y.adf_t));
+            out.extend(best);
+        }
+    }
+    out.sort_by(|x, y| x.adf_t.total_cmp(&y.adf_t));
+    out
+}
+
+fn test_pair(cfg: &SelectCfg, a: &[f64], b: &[f64], dt: f64) -> Option<Candidate> {
+    let ones = vec![1.0; b.len()];
+    let fit = regress(a, &[&ones, b])?;
+    let (alpha, beta) = (fit.coef[0], fit.coef[1]);
+    let resid: Vec<f64> = a.iter().zip(b).map(|(a, b)| a - alpha - beta * b).collect();
+
+    let adf_t = adf(&resid, cfg.adf_lags)?;
+    let half_life = half_life(&resid)?;
+    let half_life_secs = half_life * dt;
+    let selected = adf_t <= cfg.max_adf_t
+        && (cfg.min_half_life_secs..=cfg.max_half_life_secs).contains(&half_life_secs);
+    Some(Candidate {
+        sym_a: String::new(),
+        sym_b: String::new(),
+        alpha,
+        beta,
+        adf_t,
+        half_life_secs,
+        lookback: ((3.0 * half_life).round() as usize).max(2),
+        selected,
+    })
+}
+
+/// ADF t-statistic of γ in `Δe_t = γ·e_{t−1} + Σ_{i=1..lags} φ_i·Δe_{t−i}`.
+pub fn adf(e: &[f64], lags: usize) -> Option<f64> {
+    let de: Vec<f64> = e.windows(2).map(|w| w[1] - w[0]).collect();
+    // Δe_t for t = lags+1.. ; de[t-1] = e[t] − e[t−1]
+    let n = de.len().checked_sub(lags)?;
+    let y: Vec<f64> = de[lags..].to_vec();
+    let level: Vec<f64> = e[lags..lags + n].to_vec();
+    let lagged: Vec<Vec<f64>> = (1..=lags).map(|i| de[lags - i..lags - i + n].to_vec()).collect();
+    let mut xs: Vec<&[f64]> = vec![&level];
+    xs.extend(lagged.iter().map(Vec::as_slice));
+    let fit = regress(&y, &xs)?;
+    Some(fit.coef[0] / fit.se[0])
+}
+
+/// Samples for a deviation of the spread to halve, from an AR(1) fit;
+/// `None` unless the spread mean-reverts.
+pub fn half_life(e: &[f64]) -> Option<f64> {
+    let de: Vec<f64> = e.windows(2).map(|w| w[1] - w[0]).collect();
+    let ones = vec![1.0; de.len()];
+    let lambda = regress(&de, &[&ones, &e[..de.len()]])?.coef[1];
+    (-1.0 < lambda && lambda < 0.0).then(|| std::f64::consts::LN_2 / -(1.0 + lambda).ln())
+}
+
+/// Least-squares coefficients and their standard errors.
+struct Regression {
+    coef: Vec<f64>,
+    se:   Vec<f64>,
+}
+
+/// Regress `y` on the columns `xs` via the normal equations.
+fn regress(y: &[f64], xs: &[&[f64]]) -> Option<Regression> {
+    let (n, k) = (y.len(), xs.len());
+    if n <= k {
+        return None;
+    }
+    let mut xtx = vec![vec![0.0; k]; k];
+    let mut xty = vec![0.0; k];
+    for i in 0..k {
+        for j in 0..k {
+            xtx[i][j] = xs[i].iter().zip(xs[j]).map(|(a, b)| a * b).sum();
+        }
+        xty[i] = xs[i].iter().zip(y).map(|(a, b)| a * b).sum();
+    }
+    let inv = invert(xtx)?;
+    let coef: Vec<f64> = (0..k).map(|i| (0..k).map(|j| inv[i][j] * xty[j]).sum()).collect();
+    let sse: f64 = (0..n)
+        .map(|t| y[t] - (0..k).map(|i| coef[i] * xs[i][t]).sum::<f64>())
+        .map(|r| r * r)
+        .sum();
+    let s2 = sse / (n - k) as f64;
+    let se = (0..k).map(|i| (s2 * inv[i][i]).sqrt()).collect();
+    Some(Regression { coef, se })
+}
+
+/// Gauss–Jordan inverse with partial pivoting; `None` if singular.
+fn invert(mut m: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let k = m.len();
+    let mut inv: Vec<Vec<f64>> = (0..k).map(|i| (0..k).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    for c in 0..k {
+        let p = (c..k).max_by(|&a, &b| m[a][c].abs().total_cmp(&m[b][c].abs()))?;
+        if m[p][c].abs() < 1e-300 {
+            return None;
+        }
+        m.swap(c, p);
+        inv.swap(c, p);
+        let d = m[c][c];
+        for j in 0..k {
+            m[c][j] /= d;
+            inv[c][j] /= d;
+        }
+        for r in (0..k).filter(|&r| r != c) {
+            let f = m[r][c];
+            for j in 0..k {
+                m[r][j] -= f * m[c][j];
+                inv[r][j] -= f * inv[c][j];
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// `base` (the text of a `Config.toml`) with the top-level `sym_a`,
+/// `sym_b`, `beta` and `lookback` set to `pick`; comments and every other
+/// line are kept.
+pub fn config_for(base: &str, pick: &Candidate) -> String {
+    let values = [
+        ("sym_a",    format!("{:?}", pick.sym_a)),
+        ("sym_b",    format!("{:?}", pick.sym_b)),
+        ("beta",     format!("{:.6}", pick.beta)),
+        ("lookback", pick.lookback.to_string()),
+    ];
+    let mut out = String::new();
+    let _ = writeln!(out, "# selected by hft-statarb-select: adf_t={:.3} half_life={:.2}s alpha={:.6}",
+                     pick.adf_t, pick.half_life_secs, pick.alpha);
+    let mut top_level = true;
+    for line in base.lines() {
+        top_level &= !line.trim_start().starts_with('[');
+        let pair = line.split_once('=').filter(|_| top_level);
+        match pair.and_then(|(key, rhs)| values.iter().find(|(k, _)| *k == key.trim()).map(|(_, v)| (key, v, rhs))) {
+            Some((key, value, rhs)) => {
+                // keep the column layout and any trailing comment
+                let comment = rhs.find('#').map_or("", |i| &rhs[i..]);
+                let _ = writeln!(out, "{}", format!("{key}= {value:<12} {comment}").trim_end());
+            }
+            None => {
+                let _ = writeln!(out, "{line}");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StratCfg;
+    use figment::providers::{Format, Toml};
+    use hft_core::Tick;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rand_distr::StandardNormal;
+    use std::time::Duration;
+
+    const UNIVERSE: [&str; 3] = ["SIMA", "SIMB", "SIMC"];
+
+    fn select_cfg() -> SelectCfg {
+        SelectCfg {
+            universe:           UNIVERSE.iter().map(|s| s.to_string()).collect(),
+            data:               String::new(),
+            adf_lags:           1,
+            max_adf_t:          -3.34,
+            min_half_life_secs: 0.0,
+            max_half_life_secs: 60.0,
+            out:                String::new(),
+        }
+    }
+
+    /// Ticks every 100 ms: SIMB and SIMC independent random walks, SIMA
+    /// tied to SIMB by log A = 0.1 + 1.2·log B + an AR(1) spread.
+    fn history(n: usize, seed: u64) -> Vec<MarketData> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut z = || rng.sample::<f64, _>(StandardNormal);
+        let t0 = Instant::now();
+        let (mut lb, mut lc, mut spread) = (100f64.ln(), 50f64.ln(), 0.0);
+        let mut md = Vec::new();
+        for k in 0..n {
+            lb += 0.002 * z();
+            lc += 0.002 * z();
+            spread = 0.9 * spread + 0.001 * z();
+            let la = 0.1 + 1.2 * lb + spread;
+            let ts = t0 + Duration::from_millis(100 * k as u64);
+            for (sym, l) in UNIVERSE.iter().zip([la, lb, lc]) {
+                let px = l.exp();
+                md.push(MarketData::Tick(Tick { symbol: sym.to_string(), bid: px - 0.001, ask: px + 0.001, ts }));
+            }
+        }
+        md
+    }
+
+    #[test]
+    fn cointegrated_pair_is_picked_and_random_walks_are_not() {
+        let ranked = rank(&select_cfg(), &history(4_000, 11));
+        assert_eq!(ranked.len(), 3);
+
+        let best = &ranked[0];
+        assert_eq!((best.sym_a.as_str(), best.sym_b.as_str()), ("SIMA", "SIMB"));
+        assert!(best.selected, "{best:?}");
+        assert!((best.beta - 1.2).abs() < 0.05, "{best:?}");
+        // true half-life: ln 2 / −ln 0.9 samples of 0.1 s
+        assert!((best.half_life_secs - 0.658).abs() < 0.15, "{best:?}");
+
+        for c in &ranked[1..] {
+            assert!(c.sym_a == "SIMC" || c.sym_b == "SIMC");
+            assert!(!c.selected, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn half_life_of_an_ar1() {
+        let phi: f64 = 0.95;
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut e = vec![0.0];
+        for _ in 0..20_000 {
+            let next = phi * e.last().unwrap() + rng.sample::<f64, _>(StandardNormal);
+            e.push(next);
+        }
+        let want = std::f64::consts::LN_2 / -phi.ln();
+        let got = half_life(&e).unwrap();
+        assert!((got - want).abs() / want < 0.1, "half-life {got} vs {want}");
+
+        // a random walk does not revert
+        let walk: Vec<f64> = e.iter().scan(0.0, |s, x| { *s += x; Some(*s) }).collect();
+        assert!(adf(&walk, 1).unwrap() > -3.34);
+    }
+
+    #[test]
+    fn written_config_passes_validation() {
+        let ranked = rank(&select_cfg(), &history(2_000, 12));
+        let pick = ranked.iter().find(|c| c.selected).unwrap();
+        let written = config_for(include_str!("../Config.toml"), pick);
+
+        let cfg = StratCfg::extract(figment::Figment::from(Toml::string(&written))).unwrap();
+        assert_eq!((cfg.sym_a.as_str(), cfg.sym_b.as_str()), (pick.sym_a.as_str(), pick.sym_b.as_str()));
+        assert!((cfg.beta - pick.beta).abs() < 1e-6);
+        assert_eq!(cfg.lookback, pick.lookback);
+        // untouched keys survive
+        assert_eq!(cfg.entry_z, 2.0);
+    }
+}
@@ -1,117 +1,113 @@
 //! Rolling-window Z-score pair-trading engine.
 
-use crate::{config::StratCfg, models::*};
-use ndarray::Array1;
+use crate::beta::{Fit, HedgeRatio};
+use crate::config::StratCfg;
+use hft_core::*;
+use tracing::{info, warn};
 
 pub struct PairTrader {
     cfg: StratCfg,
-    // rolling log-price windows
-    log_a: Vec<f64>,
-    log_b: Vec<f64>,
+    // rolling window of (log P_A, log P_B)
+    window: RollingCov,
     // live inventory per leg
     pos_a: f64,
     pos_b: f64,
+    ids:   OrderIds,
+    // latest price per leg; the signal runs once both have ticked again
+    last_a: f64,
+    last_b: f64,
+    fresh_a: bool,
+    fresh_b: bool,
+    // hedge ratio of A on B
+    hedge: HedgeRatio,
 }
 
 /* ----- public helpers for outside code ----- */
 impl PairTrader {
+    pub fn cfg(&self) -> &StratCfg { &self.cfg }
     pub fn pos_a(&self) -> f64 { self.pos_a }
     pub fn pos_b(&self) -> f64 { self.pos_b }
+    pub fn hedge_ratio(&self) -> &HedgeRatio { &self.hedge }
 }
 
 /* ----- core logic ----- */
 impl PairTrader {
     pub fn new(cfg: StratCfg) -> Self {
-        let look = cfg.lookback;
+        let hedge = HedgeRatio::new(&cfg);
         Self {
+            window: RollingCov::new(2, cfg.lookback),
             cfg,
-            log_a: Vec::with_capacity(look),
-            log_b: Vec::with_capacity(look),
             pos_a: 0.0,
             pos_b: 0.0,
+            ids: OrderIds::default(),
+            last_a: 100.0,
+            last_b: 98.0,
+            fresh_a: false,
+            fresh_b: false,
+            hedge,
         }
     }
 
+    /// Number orders from `ids`, so several traders can share one venue.
+    pub fn ids(mut self, ids: OrderIds) -> Self {
+        self.ids = ids;
+        self
+    }
+
     /// Consume latest prices, produce zero or more orders.
     pub fn on_ticks(&mut self, a_px: f64, b_px: f64) -> Vec<Order> {
-        // update rolling window (pop oldest if full)
-        if self.log_a.len() == self.cfg.lookback {
-            self.log_a.remove(0);
-            self.log_b.remove(0);
-        }
-        self.log_a.push(a_px.ln());
-        self.log_b.push(b_px.ln());
+        // update rolling window (drops the oldest once full)
+        let (la, lb) = (a_px.ln(), b_px.ln());
+        self.window.push(&[la, lb]);
+
+        let Fit { beta, .. } = self.hedge.update(la, lb, &self.window);
 
-        if self.log_a.len() < self.cfg.lookback {
+        if !self.window.is_full() {
             return vec![];
         }
 
-        // compute current spread Z-score
-        let la = Array1::from(self.log_a.clone());
-        let lb = Array1::from(self.log_b.clone());
-        let spread = &la - &(lb * self.cfg.beta);
-
-        let mean = spread.mean().unwrap();
-        let std  = spread.std(0.0).max(1e-8);
-        let z    = (spread.last().unwrap() - mean) / std;
+        // current spread Z-score, from the window's moments under β
+        let w    = &self.window;
+        let mean = w.mean(0) - beta * w.mean(1);
+        let var  = w.var(0) + beta * beta * w.var(1) - 2.0 * beta * w.cov(0, 1);
+        let std  = var.max(0.0).sqrt().max(1e-8);
+        let z    = (la - beta * lb - mean) / std;
 
         let mut orders = Vec::new();
 
-        // ----- entry logic -----
-        if z > self.cfg.entry_z && self.pos_a - self.cfg.size >= -self.cfg.pos_limit {
+        // ----- entry logic (a negative β leaves nothing to hedge with) -----
+        let hedgeable = beta > 0.0;
+        if hedgeable && z > self.cfg.entry_z && self.pos_a - self.cfg.size >= -self.cfg.pos_limit {
             // short spread → sell A, buy B*β
-            orders.push(Order {
-                symbol: self.cfg.sym_a.clone(),
-                px: a_px,
-                qty: self.cfg.size,
-                side: Side::Sell,
-            });
-            orders.push(Order {
-                symbol: self.cfg.sym_b.clone(),
-                px: b_px,
-                qty: self.cfg.size * self.cfg.beta,
-                side: Side::Buy,
-            });
-        } else if z < -self.cfg.entry_z && self.pos_a + self.cfg.size <= self.cfg.pos_limit {
+            orders.push(Order::market(self.ids.next(), &self.cfg.sym_a, Side::Sell, self.cfg.size));
+            orders.push(Order::market(
+                self.ids.next(), &self.cfg.sym_b, Side::Buy, self.cfg.size * beta,
+            ));
+        } else if hedgeable && z < -self.cfg.entry_z && self.pos_a + self.cfg.size <= self.cfg.pos_limit {
             // long spread → buy A, sell B*β
-            orders.push(Order {
-                symbol: self.cfg.sym_a.clone(),
-                px: a_px,
-                qty: self.cfg.size,
-                side: Side::Buy,
-            });
-            orders.push(Order {
-                symbol: self.cfg.sym_b.clone(),
-                px: b_px,
-                qty: self.cfg.size * self.cfg.beta,
-                side: Side::Sell,
-            });
+            orders.push(Order::market(self.ids.next(), &self.cfg.sym_a, Side::Buy, self.cfg.size));
+            orders.push(Order::market(
+                self.ids.next(), &self.cfg.sym_b, Side::Sell, self.cfg.size * beta,
+            ));
         }
 
         // ----- exit / flatten logic -----
         if z.abs() < self.cfg.exit_z && (self.pos_a != 0.0 || self.pos_b != 0.0) {
-            let dir_a = if self.pos_a > 0.0 { Side::Sell } else { Side::Buy };
-            let dir_b = if self.pos_b > 0.0 { Side::Sell } else { Side::Buy };
-            orders.push(Order {
-                symbol: self.cfg.sym_a.clone(),
-                px: a_px,
-                qty: self.pos_a.abs(),
-                side: dir_a,
-            });
-            orders.push(Order {
-                symbol: self.cfg.sym_b.clone(),
-                px: b_px,
-                qty: self.pos_b.abs(),
-                side: dir_b,
-            });
+            // a leg the risk gate held back may be flat already
+            for (sym, pos) in [(&self.cfg.sym_a, self.pos_a), (&self.cfg.sym_b, self.pos_b)] {
+                if pos != 0.0 {
+                    let dir = if pos > 0.0 { Side::Sell } else { Side::Buy };
+                    orders.push(Order::market(self.ids.next(), sym, dir, pos.abs()));
+                }
+            }
         }
 
         orders
     }
 
     /// Update internal inventory after a fill.
-    pub fn on_fill(&mut self, f: &Fill) {
-        let delta = if matches!(f.side, Side::Buy) { f.qty } else { -f.qty };
+    fn on_fill(&mut self, f: &Fill) {
+        let delta = f.side.sign() * f.qty;
         if f.symbol == self.cfg.sym_a {
             self.pos_a += delta;
         } else if f.symbol == self.cfg.sym_b {
@@ -119,3 +115,36 @@ impl PairTrader {
         }
     }
 }
+
+impl Strategy for PairTrader {
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        let MarketData::Tick(tick) = md else { return Vec::new() };
+        if tick.symbol == self.cfg.sym_a {
+            self.last_a = tick.mid();
+            self.fresh_a = true;
+        } else if tick.symbol == self.cfg.sym_b {
+            self.last_b = tick.mid();
+            self.fresh_b = true;
+        }
+
+        // run the signal only once both legs have a new price
+        if !(self.fresh_a && self.fresh_b) {
+            return Vec::new();
+        }
+        (self.fresh_a, self.fresh_b) = (false, false);
+        self.on_ticks(self.last_a, self.last_b).into_iter().map(OrderReq::New).collect()
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        match er {
+            ExecReport::Fill(fill) => {
+                self.on_fill(fill);
+                info!("FILL #{} {:?} {:.2} {} @ {:.2} fee={:.4} leaves={:.2}",
+                      fill.id, fill.side, fill.qty, fill.symbol, fill.px, fill.fee, fill.leaves);
+            }
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            _ => {}
+        }
+        Vec::new()
+    }
+}
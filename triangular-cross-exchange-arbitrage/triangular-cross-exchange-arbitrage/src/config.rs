@@ -1,6 +1,7 @@
 //! Load user knobs.
 
 use anyhow::Result;
+use hft_core::FeeSchedule;
 use figment::providers::{Env, Format, Toml};
 use serde::Deserialize;
 use std::collections::BTreeMap;
@@ -8,18 +9,31 @@ use std::collections::BTreeMap;
 #[derive(Debug, Deserialize, Clone)]
 pub struct TriCfg {
     pub entry_bps:  f64,
+    #[allow(dead_code)]     // no exit leg yet: triangles close themselves
     pub exit_bps:   f64,
-    pub fee_bps:    f64,
+    #[serde(default)]
+    pub fees:       FeeSchedule,     // venue fees, in quote currency; also priced into each leg
     pub size_eur:   f64,
     pub pos_limit:  f64,
     pub tick_ms:    u64,
+    pub seed:       u64,
+    #[serde(default)]
+    pub backtest:   bool,            // virtual clock, no wall-clock pacing
+    pub max_ticks:  Option<u64>,     // end the session after N ticks
+    pub record:     Option<String>,  // JSONL of market data + executions
+    pub replay:     Option<String>,  // replay a recording instead of the sim
+    pub data:       Option<String>,  // historical CSV/Parquet instead of the sim
+    pub report_bucket_ms: u64,       // Sharpe/Sortino bucket of the end-of-run report
+    pub report_json: Option<String>, // also write the report as JSON
     pub spreads:    BTreeMap<String, f64>,
 }
 
 impl TriCfg {
     pub fn load() -> Result<Self> {
-        Ok(figment::Figment::from(Toml::file("Config.toml"))
+        let cfg: Self = figment::Figment::from(Toml::file("Config.toml"))
             .merge(Env::prefixed("TRI_"))
-            .extract()?)
+            .extract()?;
+        anyhow::ensure!(cfg.report_bucket_ms > 0, "report_bucket_ms must be positive");
+        Ok(cfg)
     }
 }
@@ -1,81 +1,166 @@
 //! One-venue simulator for EUR/USD, USD/JPY and EUR/JPY.
 
-use crate::models::*;
-use rand::random;                               // only `random()` is used
+use hft_core::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::BTreeMap;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tracing::info;
 
 pub struct Exchange {
-    md_tx:   mpsc::Sender<Tick>,
-    fill_tx: mpsc::Sender<Fill>,
+    md_tx:   mpsc::Sender<MarketData>,
+    er_tx:   mpsc::Sender<ExecReport>,
     mid:     BTreeMap<String, f64>,              // pair → mid-price
     spread:  BTreeMap<String, f64>,              // pair → fixed spread
+    rng:     StdRng,                             // sole source of randomness
+    now:     Instant,                            // session time of the current step
+    schedule: FeeSchedule,                       // what every fill is charged
+    fees:    BTreeMap<String, FeeMeter>,         // quote currency → its fee account
 }
 
 impl Exchange {
     pub fn new(
-        md_tx: mpsc::Sender<Tick>,
-        fill_tx: mpsc::Sender<Fill>,
+        md_tx: mpsc::Sender<MarketData>,
+        er_tx:   mpsc::Sender<ExecReport>,
         spreads: &BTreeMap<String, f64>,
         start_eur_usd: f64,
         start_usd_jpy: f64,
+        seed: u64,
     ) -> Self {
         let mut mid = BTreeMap::new();
         mid.insert("EUR/USD".into(), start_eur_usd);
         mid.insert("USD/JPY".into(), start_usd_jpy);
         mid.insert("EUR/JPY".into(), start_eur_usd * start_usd_jpy);
 
-        Self { md_tx, fill_tx, mid, spread: spreads.clone() }
+        Self {
+            md_tx,
+            er_tx,
+            mid,
+            spread: spreads.clone(),
+            rng: StdRng::seed_from_u64(seed),
+            now: Instant::now(),
+            schedule: FeeSchedule::default(),
+            fees: BTreeMap::new(),
+        }
+    }
+
+    /// Charge fills with `fees` (none by default). Tiers count notional per
+    /// quote currency, as USD and JPY amounts don't add up.
+    pub fn fees(mut self, fees: FeeSchedule) -> Self {
+        self.schedule = fees;
+        self.fees.clear();
+        self
     }
 
-    pub async fn run(mut self, tick_ms: u64, mut od_rx: mpsc::Receiver<Order>) {
-        let mut intv = tokio::time::interval(std::time::Duration::from_millis(tick_ms));
+    pub async fn run(mut self, mut clock: Clock, mut od_rx: mpsc::Receiver<OrderReq>,
+                     mut done_rx: mpsc::Receiver<()>) {
 
         loop {
             tokio::select! {
+                // requests already queued are handled before the next tick
+                biased;
+
+                /* ---- fill market orders immediately at touch ---- */
+                Some(req) = od_rx.recv() => {
+                    for er in self.on_request(req) {
+                        let _ = self.er_tx.send(er).await;
+                    }
+                }
+
                 /* ---- publish ticks ---- */
-                _ = intv.tick() => {
+                now = clock.tick() => {
+                    // ticks carry session time, not wall time
+                    let Some(now) = now else { break };
+                    self.now = now;
+
                     // tiny random walk on the two legs
                     for &(pair, vol) in &[("EUR/USD", 0.00005), ("USD/JPY", 0.005)] {
-                        let bump = (random::<f64>() - 0.5) * vol;
+                        let bump = (self.rng.gen::<f64>() - 0.5) * vol;
                         *self.mid.get_mut(pair).unwrap() *= 1.0 + bump;
                     }
                     // create small mis-pricing on EUR/JPY
-                    let off = (random::<f64>() - 0.5) * 0.002;
+                    let off = (self.rng.gen::<f64>() - 0.5) * 0.002;
                     self.mid.insert(
                         "EUR/JPY".into(),
                         self.mid["EUR/USD"] * self.mid["USD/JPY"] * (1.0 + off),
                     );
 
-                    let now = std::time::Instant::now();
                     for (pair, mid) in &self.mid {
                         let spr = self.spread[pair];
-                        let _ = self.md_tx.send(Tick {
-                            pair: pair.clone(),
-                            bid:  mid - spr / 2.0,
-                            ask:  mid + spr / 2.0,
-                            ts:   now,
-                        }).await;
+                        let _ = self.md_tx.send(MarketData::Tick(Tick {
+                            symbol: pair.clone(),
+                            bid:    mid - spr / 2.0,
+                            ask:    mid + spr / 2.0,
+                            ts:     now,
+                        })).await;
                     }
-                }
-
-                /* ---- fill market orders immediately at touch ---- */
-                Some(o) = od_rx.recv() => {
-                    let spr   = self.spread[&o.pair];
-                    let mid   = self.mid[&o.pair];
-                    let price = if matches!(o.side, Side::Buy) { mid + spr/2.0 }
-                                else                            { mid - spr/2.0 };
 
-                    let _ = self.fill_tx.send(Fill {
-                        pair:     o.pair.clone(),
-                        side:     o.side.clone(),
-                        qty_base: o.qty_base,
-                        price,
-                    }).await;
+                    // wait for the strategy to react before stepping again
+                    if !self.settle(self.mid.len(), &mut od_rx, &mut done_rx).await {
+                        break;
+                    }
                 }
 
                 else => break,
             }
         }
+        info!("session over after {} ticks", clock.ticks());
+    }
+
+    /// Lockstep with the strategy: wait until it has reacted to the `n`
+    /// ticks just published, then take in everything it sent before
+    /// stepping again. This keeps a seeded run identical however fast
+    /// either side is.
+    async fn settle(&mut self,
+                    n: usize,
+                    od_rx: &mut mpsc::Receiver<OrderReq>,
+                    done_rx: &mut mpsc::Receiver<()>) -> bool
+    {
+        for _ in 0..n {
+            if done_rx.recv().await.is_none() {
+                return false;
+            }
+        }
+        while let Ok(req) = od_rx.try_recv() {
+            for er in self.on_request(req) {
+                let _ = self.er_tx.send(er).await;
+            }
+        }
+        true
+    }
+
+    /// Market orders fill at the touch on arrival, so there is never
+    /// anything left resting to cancel or amend.
+    fn on_request(&mut self, req: OrderReq) -> Vec<ExecReport> {
+        match req {
+            OrderReq::New(o) => {
+                let (Some(spr), Some(mid)) = (self.spread.get(&o.symbol), self.mid.get(&o.symbol)) else {
+                    return vec![ExecReport::Reject { id: o.id, reason: "unknown pair".into() }];
+                };
+                let px = if matches!(o.side, Side::Buy) { mid + spr/2.0 }
+                         else                            { mid - spr/2.0 };
+                let quote = o.symbol.rsplit('/').next().unwrap_or_default().to_string();
+                let meter = self.fees.entry(quote)
+                    .or_insert_with(|| FeeMeter::new(self.schedule.clone()));
+                vec![
+                    ExecReport::NewOrderAck { id: o.id },
+                    ExecReport::Fill(meter.charge(Fill {
+                        id:     o.id,
+                        symbol: o.symbol,
+                        side:   o.side,
+                        px,
+                        qty:    o.qty,       // base units
+                        leaves: 0.0,
+                        liquidity: Liquidity::Taker,
+                        fee:    0.0,         // quote currency, set by the meter
+                        ts:     self.now,
+                    })),
+                ]
+            }
+            OrderReq::Cancel { id } | OrderReq::Replace { id, .. } => {
+                vec![ExecReport::Reject { id, reason: "unknown or closed order".into() }]
+            }
+        }
     }
 }
@@ -0,0 +1,6 @@
+//! EUR/USD/JPY triangular arbitrage and its single-venue FX simulator.
+
+pub mod config;
+pub mod exchange;
+pub mod risk;
+pub mod strategy;
@@ -1,14 +1,8 @@
-mod config;
-mod models;
-mod exchange;
-mod strategy;
-mod risk;
-
 use anyhow::Result;
-use tokio::sync::mpsc;
-use tracing::info;
 
-use crate::{config::TriCfg, models::*};
+use hft_core::*;
+use hft_triarb::{config::TriCfg, exchange, risk, strategy::TriArb};
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -16,42 +10,51 @@ async fn main() -> Result<()> {
     let cfg = TriCfg::load()?;
 
     // channels
-    let (md_tx, mut md_rx)   = mpsc::channel::<Tick>(2048);
-    let (fill_tx, mut fills) = mpsc::channel::<Fill>(2048);
-    let (od_tx,  od_rx)      = mpsc::channel::<Order>(2048);
+    let (venue, engine) = link(2048);
+
+    // spawn exchange (or replay a recording)
+    let clock  = Clock::new(cfg.tick_ms, cfg.backtest, cfg.max_ticks);
+    let origin = clock.start();
+    match (&cfg.replay, &cfg.data) {
+        (Some(path), _) => {
+            let rp = Replay::open(path, venue.md_tx, venue.er_tx)?.fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, Some(path)) => {
+            let syms: Vec<&str> = cfg.spreads.keys().map(String::as_str).collect();
+            let md = history::load(path, &syms, origin)?;
+            let rp = Replay::new(md, venue.md_tx, venue.er_tx).fees(cfg.fees.clone());
+            tokio::spawn(rp.run(venue.od_rx, venue.done_rx));
+        }
+        (None, None) => {
+            tokio::spawn(
+                exchange::Exchange::new(venue.md_tx, venue.er_tx, &cfg.spreads, 1.10, 150.0, cfg.seed)
+                    .fees(cfg.fees.clone())
+                    .run(clock, venue.od_rx, venue.done_rx)
+            );
+        }
+    }
 
-    // spawn exchange
-    tokio::spawn(
-        exchange::Exchange::new(md_tx, fill_tx, &cfg.spreads, 1.10, 150.0)
-            .run(cfg.tick_ms, od_rx)
-    );
+    // end-of-run report
+    let mut perf = Performance::new("hft-triarb", Duration::from_millis(cfg.report_bucket_ms));
+    if let Some(path) = &cfg.report_json {
+        perf = perf.json(path);
+    }
 
     // strategy + risk
-    let mut strat  = strategy::TriArb::new(cfg.clone());
-    let riskmgr    = risk::Risk::new(cfg.clone());
-
-    loop {
-        tokio::select! {
-            Some(t) = md_rx.recv() => {
-                strat.update_tick(t);
-                let orders = strat.check();
-
-                for o in orders {
-                    let pos_after = strat.pos_after_exec(&o);
-                    if riskmgr.allow(&pos_after) {
-                        od_tx.send(o).await?;
-                    }
-                }
-            }
-
-            Some(f) = fills.recv() => {
-                strat.on_fill(&f);
-                info!("FILL {:?} {:.0} {} at {:.4}", f.side, f.qty_base, f.pair, f.price);
-            }
-
-            else => break,
-        }
+    let strat   = TriArb::new(cfg.clone());
+    let riskmgr = risk::Risk::new(cfg.clone());
+
+    let mut engine = Engine::new(strat, engine)
+        .risk(move |strat: &TriArb, req: &OrderReq, _| match req {
+            OrderReq::New(o) => riskmgr.allow(&strat.pos_after_exec(o)),
+            _ => true,
+        })
+        .report(perf);
+    if let Some(path) = &cfg.record {
+        engine = engine.record(Recorder::create(path, origin)?);
     }
+    engine.run().await;
 
     Ok(())
 }
@@ -1,12 +1,16 @@
 //! EUR → USD → JPY → EUR triangular-arbitrage engine.
 
-use crate::{config::TriCfg, models::*};
+use crate::config::TriCfg;
+use hft_core::*;
 use std::collections::{BTreeMap, HashMap};
+use tracing::{info, warn};
 
 pub struct TriArb {
     cfg: TriCfg,
     px:  BTreeMap<String, (f64, f64)>,   // pair → (bid, ask)
     pos: HashMap<&'static str, f64>,     // currency → inventory
+    vol: HashMap<String, f64>,           // quote currency → notional traded, for fee tiers
+    ids: OrderIds,
 }
 
 impl TriArb {
@@ -15,32 +19,42 @@ impl TriArb {
             cfg,
             px:  BTreeMap::new(),
             pos: [("EUR", 0.0), ("USD", 0.0), ("JPY", 0.0)].into(),
+            vol: HashMap::new(),
+            ids: OrderIds::default(),
         }
     }
 
     /* ────────── market-data ────────── */
-    pub fn update_tick(&mut self, t: Tick) { self.px.insert(t.pair, (t.bid, t.ask)); }
+    pub fn update_tick(&mut self, t: Tick) { self.px.insert(t.symbol, (t.bid, t.ask)); }
+
+    /// Taker fee on `pair` at `px`, as a fraction of notional, at the tier
+    /// our volume in its quote currency has reached.
+    fn taker_fee(&self, pair: &str, px: f64) -> f64 {
+        let quote = pair.rsplit('/').next().unwrap_or_default();
+        let rates = self.cfg.fees.rates(self.vol.get(quote).copied().unwrap_or(0.0));
+        rates.taker_bps / 10_000.0 + rates.taker_per_unit / px
+    }
 
     /* ────────── signal logic ───────── */
-    pub fn check(&self) -> Vec<Order> {
+    pub fn check(&mut self) -> Vec<Order> {
         if self.px.len() < 3 { return vec![]; }
 
         let (eu_b, eu_a) = self.px["EUR/USD"];
         let (uj_b, uj_a) = self.px["USD/JPY"];
         let (ej_b, ej_a) = self.px["EUR/JPY"];
 
-        let fee = self.cfg.fee_bps / 10_000.0;
+        let fee = |pair, px| self.taker_fee(pair, px);
         let qty = self.cfg.size_eur;
 
         // path-1 EUR→USD→JPY→EUR
-        let eur_out1 = qty * eu_b * (1.0 - fee)
-                           * uj_b * (1.0 - fee)
-                       / ej_a / (1.0 + fee);
+        let eur_out1 = qty * eu_b * (1.0 - fee("EUR/USD", eu_b))
+                           * uj_b * (1.0 - fee("USD/JPY", uj_b))
+                       / ej_a / (1.0 + fee("EUR/JPY", ej_a));
 
         // path-2 EUR→JPY→USD→EUR
-        let eur_out2 = qty * ej_b * (1.0 - fee)
-                       / uj_a / (1.0 + fee)
-                       / eu_a / (1.0 + fee);
+        let eur_out2 = qty * ej_b * (1.0 - fee("EUR/JPY", ej_b))
+                       / uj_a / (1.0 + fee("USD/JPY", uj_a))
+                       / eu_a / (1.0 + fee("EUR/USD", eu_a));
 
         let edge1 = (eur_out1 / qty - 1.0) * 10_000.0;
         let edge2 = (eur_out2 / qty - 1.0) * 10_000.0;
@@ -48,91 +62,96 @@ impl TriArb {
         let mut orders = Vec::new();
 
         if edge1 > self.cfg.entry_bps {
-            orders.push(Order { pair:"EUR/USD".into(), side:Side::Sell, qty_base:qty });
-            orders.push(Order { pair:"USD/JPY".into(), side:Side::Buy,  qty_base:qty * eu_b });
-            orders.push(Order { pair:"EUR/JPY".into(), side:Side::Buy,  qty_base:qty });
+            orders.push(Order::market(self.ids.next(), "EUR/USD", Side::Sell, qty));
+            orders.push(Order::market(self.ids.next(), "USD/JPY", Side::Buy, qty * eu_b));
+            orders.push(Order::market(self.ids.next(), "EUR/JPY", Side::Buy, qty));
         } else if edge2 > self.cfg.entry_bps {
-            orders.push(Order { pair:"EUR/JPY".into(), side:Side::Sell, qty_base:qty });
-            orders.push(Order { pair:"USD/JPY".into(), side:Side::Sell,
-                                qty_base:qty * ej_b / eu_a });
-            orders.push(Order { pair:"EUR/USD".into(), side:Side::Buy,  qty_base:qty });
+            orders.push(Order::market(self.ids.next(), "EUR/JPY", Side::Sell, qty));
+            orders.push(Order::market(self.ids.next(), "USD/JPY", Side::Sell, qty * ej_b / eu_a));
+            orders.push(Order::market(self.ids.next(), "EUR/USD", Side::Buy, qty));
         }
         orders
     }
 
     /* ────────── fill processing ─────── */
-    pub fn on_fill(&mut self, f: &Fill) {
-        match f.pair.as_str() {
+    fn on_fill(&mut self, f: &Fill) {
+        match f.symbol.as_str() {
             /* EUR/USD -------------------------------------------------- */
             "EUR/USD" => {
-                let usd = f.qty_base * f.price;
+                let usd = f.qty * f.px;
                 if matches!(f.side, Side::Buy) {
-                    *self.pos.get_mut("EUR").unwrap() += f.qty_base;
+                    *self.pos.get_mut("EUR").unwrap() += f.qty;
                     *self.pos.get_mut("USD").unwrap() -= usd;
                 } else {
-                    *self.pos.get_mut("EUR").unwrap() -= f.qty_base;
+                    *self.pos.get_mut("EUR").unwrap() -= f.qty;
                     *self.pos.get_mut("USD").unwrap() += usd;
                 }
             }
             /* USD/JPY -------------------------------------------------- */
             "USD/JPY" => {
-                let jpy = f.qty_base * f.price;
+                let jpy = f.qty * f.px;
                 if matches!(f.side, Side::Buy) {
-                    *self.pos.get_mut("USD").unwrap() -= f.qty_base;
+                    *self.pos.get_mut("USD").unwrap() -= f.qty;
                     *self.pos.get_mut("JPY").unwrap() += jpy;
                 } else {
-                    *self.pos.get_mut("USD").unwrap() += f.qty_base;
+                    *self.pos.get_mut("USD").unwrap() += f.qty;
                     *self.pos.get_mut("JPY").unwrap() -= jpy;
                 }
             }
             /* EUR/JPY -------------------------------------------------- */
             "EUR/JPY" => {
-                let jpy = f.qty_base * f.price;
+                let jpy = f.qty * f.px;
                 if matches!(f.side, Side::Buy) {
-                    *self.pos.get_mut("EUR").unwrap() += f.qty_base;
+                    *self.pos.get_mut("EUR").unwrap() += f.qty;
                     *self.pos.get_mut("JPY").unwrap() -= jpy;
                 } else {
-                    *self.pos.get_mut("EUR").unwrap() -= f.qty_base;
+                    *self.pos.get_mut("EUR").unwrap() -= f.qty;
                     *self.pos.get_mut("JPY").unwrap() += jpy;
                 }
             }
             _ => {}
         }
+        // the venue charges in the pair's quote currency, and tiers on it
+        let quote = f.symbol.rsplit('/').next().unwrap_or_default();
+        if let Some(ccy) = self.pos.get_mut(quote) {
+            *ccy -= f.fee;
+        }
+        *self.vol.entry(quote.to_string()).or_default() += f.px * f.qty;
     }
 
     /* ── inventory after hypothetical exec (risk check helper) ── */
     pub fn pos_after_exec(&self, ord: &Order) -> HashMap<&'static str, f64> {
         let mut p = self.pos.clone();
-        let bid = self.px[&ord.pair].0;
+        let bid = self.px[&ord.symbol].0;
 
-        match ord.pair.as_str() {
+        match ord.symbol.as_str() {
             "EUR/USD" => {
-                let usd = ord.qty_base * bid;
+                let usd = ord.qty * bid;
                 if matches!(ord.side, Side::Buy) {
-                    *p.get_mut("EUR").unwrap() += ord.qty_base;
+                    *p.get_mut("EUR").unwrap() += ord.qty;
                     *p.get_mut("USD").unwrap() -= usd;
                 } else {
-                    *p.get_mut("EUR").unwrap() -= ord.qty_base;
+                    *p.get_mut("EUR").unwrap() -= ord.qty;
                     *p.get_mut("USD").unwrap() += usd;
                 }
             }
             "USD/JPY" => {
-                let jpy = ord.qty_base * bid;
+                let jpy = ord.qty * bid;
                 if matches!(ord.side, Side::Buy) {
-                    *p.get_mut("USD").unwrap() -= ord.qty_base;
+                    *p.get_mut("USD").unwrap() -= ord.qty;
                     *p.get_mut("JPY").unwrap() += jpy;
                 } else {
-                    *p.get_mut("USD").unwrap() += ord.qty_base;
+                    *p.get_mut("USD").unwrap() += ord.qty;
                     *p.get_mut("JPY").unwrap() -= jpy;
                 }
             }
             "EUR/JPY" => {
-                let jpy = ord.qty_base * bid;
+                let jpy = ord.qty * bid;
                 if matches!(ord.side, Side::Buy) {
-                    *p.get_mut("EUR").unwrap() += ord.qty_base;
+                    *p.get_mut("EUR").unwrap() += ord.qty;
                     *p.get_mut("JPY").unwrap() -= jpy;
                 } else {
-                    *p.get_mut("EUR").unwrap() -= ord.qty_base;
+                    *p.get_mut("EUR").unwrap() -= ord.qty;
                     *p.get_mut("JPY").unwrap() += jpy;
                 }
             }
@@ -141,3 +160,24 @@ impl TriArb {
         p
     }
 }
+
+impl Strategy for TriArb {
+    fn on_market_data(&mut self, md: &MarketData) -> Vec<OrderReq> {
+        let MarketData::Tick(t) = md else { return Vec::new() };
+        self.update_tick(t.clone());
+        self.check().into_iter().map(OrderReq::New).collect()
+    }
+
+    fn on_execution(&mut self, er: &ExecReport) -> Vec<OrderReq> {
+        match er {
+            ExecReport::Fill(f) => {
+                self.on_fill(f);
+                info!("FILL #{} {:?} {:.0} {} at {:.4} fee={:.4} leaves={:.0}",
+                      f.id, f.side, f.qty, f.symbol, f.px, f.fee, f.leaves);
+            }
+            ExecReport::Reject { id, reason } => warn!("REJECT #{id} {reason}"),
+            _ => {}
+        }
+        Vec::new()
+    }
+}